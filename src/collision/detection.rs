@@ -5,7 +5,7 @@
 //! - Actual collisions in narrow phase, that produce a manifold
 
 use crate::{
-    collision::{Hitbox, Manifold},
+    collision::{Hitbox, Manifold, SolverSettings},
     entities::body::Body,
     math::{
         vector2::{dot, NORMAL_DOWN, NORMAL_LEFT, NORMAL_RIGHT, NORMAL_UP, ZERO},
@@ -21,22 +21,22 @@ pub fn hitboxes_collide(a: &Body, b: &Body) -> bool {
     let hitbox_a: Hitbox = &a.hitbox + a.transform.location;
     let hitbox_b: Hitbox = &b.hitbox + b.transform.location;
 
-    if hitbox_a.min.x >= hitbox_b.max.x || hitbox_b.min.x >= hitbox_a.max.x {
-        return false;
-    }
-
-    if hitbox_a.min.y >= hitbox_b.max.y || hitbox_b.min.y >= hitbox_a.max.y {
-        return false;
-    }
-
-    true
+    hitbox_a.overlaps(&hitbox_b)
 }
 
 // --------------------------------- NARROW PHASE ---------------------------------
 /// Detects collision between 2 bodies (a, b) living in the world.<br>
 /// If a collision is detected, the function generates a manifold that
 /// can be used to resolve the collision later on.
-pub fn detect_collision(a: &Body, a_idx: usize, b: &Body, b_idx: usize) -> Option<Manifold> {
+pub fn detect_collision(
+    a: &Body,
+    a_idx: usize,
+    b: &Body,
+    b_idx: usize,
+    settings: &SolverSettings,
+) -> Option<Manifold> {
+    let tolerance = settings.get_contact_tolerance();
+
     match a.shape {
         Circle(_) => match b.shape {
             Circle(_) => circle_circle(a, a_idx, b, b_idx),
@@ -46,14 +46,14 @@ pub fn detect_collision(a: &Body, a_idx: usize, b: &Body, b_idx: usize) -> Optio
 
         AABB(_) => match b.shape {
             Circle(_) => circle_aabb(b, b_idx, a, a_idx),
-            AABB(_) => aabb_aabb(a, a_idx, b, b_idx),
-            Polygon(_) => aabb_polygon(a, a_idx, b, b_idx),
+            AABB(_) => aabb_aabb(a, a_idx, b, b_idx, tolerance),
+            Polygon(_) => aabb_polygon(a, a_idx, b, b_idx, tolerance),
         },
 
         Polygon(_) => match b.shape {
             Circle(_) => circle_polygon(b, b_idx, a, a_idx),
-            AABB(_) => aabb_polygon(b, b_idx, a, a_idx),
-            Polygon(_) => polygon_polygon(a, a_idx, b, b_idx),
+            AABB(_) => aabb_polygon(b, b_idx, a, a_idx, tolerance),
+            Polygon(_) => polygon_polygon(a, a_idx, b, b_idx, tolerance),
         },
     }
 }
@@ -192,7 +192,7 @@ fn circle_polygon(
     Some(m)
 }
 
-fn aabb_aabb(a: &Body, a_idx: usize, b: &Body, b_idx: usize) -> Option<Manifold> {
+fn aabb_aabb(a: &Body, a_idx: usize, b: &Body, b_idx: usize, tolerance: f32) -> Option<Manifold> {
     let mut m = Manifold::new(a, a_idx, b, b_idx);
 
     let direction = b.transform.location - a.transform.location;
@@ -221,7 +221,7 @@ fn aabb_aabb(a: &Body, a_idx: usize, b: &Body, b_idx: usize) -> Option<Manifold>
         depth = x_overlap;
     }
 
-    let (contact_1, contact_2) = contacts_double(a, b);
+    let (contact_1, contact_2) = contacts_double(a, b, tolerance);
 
     m.contact_count = 1;
     m.depth = depth;
@@ -241,11 +241,18 @@ fn aabb_polygon(
     aabb_idx: usize,
     polygon: &Body,
     polygon_idx: usize,
+    tolerance: f32,
 ) -> Option<Manifold> {
-    polygon_polygon(aabb, aabb_idx, polygon, polygon_idx)
+    polygon_polygon(aabb, aabb_idx, polygon, polygon_idx, tolerance)
 }
 
-fn polygon_polygon(a: &Body, a_idx: usize, b: &Body, b_idx: usize) -> Option<Manifold> {
+fn polygon_polygon(
+    a: &Body,
+    a_idx: usize,
+    b: &Body,
+    b_idx: usize,
+    tolerance: f32,
+) -> Option<Manifold> {
     let (vertices_a, len_a) = a.get_moved_vertices();
     let (vertices_b, len_b) = b.get_moved_vertices();
 
@@ -299,7 +306,7 @@ fn polygon_polygon(a: &Body, a_idx: usize, b: &Body, b_idx: usize) -> Option<Man
     let mut m = Manifold::new(a, a_idx, b, b_idx);
 
     let direction = b.transform.location - a.transform.location;
-    let (contact_1, contact_2) = contacts_double(a, b);
+    let (contact_1, contact_2) = contacts_double(a, b, tolerance);
 
     if direction.dotted(normal) < 0.0 {
         normal *= -1.;
@@ -363,7 +370,7 @@ fn contacts_single(p: Vector2, body: &Body) -> Vector2 {
     contact
 }
 
-fn contacts_double(a: &Body, b: &Body) -> (Vector2, Option<Vector2>) {
+fn contacts_double(a: &Body, b: &Body, tolerance: f32) -> (Vector2, Option<Vector2>) {
     let (vertices_a, len_a) = a.get_moved_vertices();
     let (vertices_b, len_b) = b.get_moved_vertices();
 
@@ -380,8 +387,10 @@ fn contacts_double(a: &Body, b: &Body) -> (Vector2, Option<Vector2>) {
             let contact_candidate: Vector2 = project_onto_line(vb_1, vb_2, va);
             let d2 = Vector2::distance_squared(va, contact_candidate);
 
-            if similar(d2, min_d2) {
-                if contact_1.is_some() && !similar_vector2(contact_1.unwrap(), contact_candidate) {
+            if similar(d2, min_d2, tolerance) {
+                if contact_1.is_some()
+                    && !similar_vector2(contact_1.unwrap(), contact_candidate, tolerance)
+                {
                     contact_2 = Some(contact_candidate);
                 }
             } else if d2 < min_d2 {
@@ -400,8 +409,10 @@ fn contacts_double(a: &Body, b: &Body) -> (Vector2, Option<Vector2>) {
             let contact_candidate = project_onto_line(va_1, va_2, vb);
             let d2 = Vector2::distance_squared(vb, contact_candidate);
 
-            if similar(d2, min_d2) {
-                if contact_1.is_some() && !similar_vector2(contact_1.unwrap(), contact_candidate) {
+            if similar(d2, min_d2, tolerance) {
+                if contact_1.is_some()
+                    && !similar_vector2(contact_1.unwrap(), contact_candidate, tolerance)
+                {
                     contact_2 = Some(contact_candidate);
                 }
             } else if d2 < min_d2 {
@@ -417,7 +428,7 @@ fn contacts_double(a: &Body, b: &Body) -> (Vector2, Option<Vector2>) {
     )
 }
 
-fn project_onto_line(a: Vector2, b: Vector2, p: Vector2) -> Vector2 {
+pub(crate) fn project_onto_line(a: Vector2, b: Vector2, p: Vector2) -> Vector2 {
     let line = b - a;
     let ap = p - a;
     let projection_factor = dot(line, ap) / dot(line, line);
@@ -455,12 +466,10 @@ fn is_outside_polygon(p: Vector2, polygon: &Body) -> bool {
     count % 2 == 0
 }
 
-const THRESHHOLD: f32 = 0.0001;
-
-pub fn similar(f1: f32, f2: f32) -> bool {
-    (f1 - f2).abs() <= THRESHHOLD
+pub fn similar(f1: f32, f2: f32, tolerance: f32) -> bool {
+    (f1 - f2).abs() <= tolerance
 }
 
-fn similar_vector2(v1: Vector2, v2: Vector2) -> bool {
-    similar(v1.x, v2.x) && similar(v1.y, v2.y)
+fn similar_vector2(v1: Vector2, v2: Vector2, tolerance: f32) -> bool {
+    similar(v1.x, v2.x, tolerance) && similar(v1.y, v2.y, tolerance)
 }
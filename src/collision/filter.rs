@@ -0,0 +1,105 @@
+//! Collision filtering, decides which pairs of bodies are allowed to collide at all.<br>
+//! Every body belongs to one or more categories and has a mask of categories it collides with.
+//! Groups override the categories: bodies sharing a positive group always collide,
+//! bodies sharing a negative group never collide.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionFilter {
+    /// Categories this body belongs to, usually a single bit.
+    pub category: u32,
+    /// Categories this body collides with.
+    pub mask: u32,
+    pub group: i32,
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        CollisionFilter {
+            category: 0x0001,
+            mask: u32::MAX,
+            group: 0,
+        }
+    }
+}
+
+impl CollisionFilter {
+    pub fn new(category: u32, mask: u32) -> CollisionFilter {
+        CollisionFilter {
+            category,
+            mask,
+            group: 0,
+        }
+    }
+
+    pub fn group(mut self, group: i32) -> Self {
+        self.group = group;
+        self
+    }
+
+    pub fn should_collide(&self, other: &CollisionFilter) -> bool {
+        if self.group != 0 && self.group == other.group {
+            return self.group > 0;
+        }
+
+        self.mask & other.category != 0 && other.mask & self.category != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{body::Body, material::DEFAULT},
+        test_utils::{run, sleeping_box, world},
+    };
+
+    #[test]
+    fn categories_have_to_match_both_masks() {
+        let player = CollisionFilter::new(0b001, 0b110);
+        let enemy = CollisionFilter::new(0b010, 0b101);
+        let pickup = CollisionFilter::new(0b100, 0b000);
+
+        assert!(player.should_collide(&enemy));
+        assert!(!player.should_collide(&pickup));
+        assert!(!pickup.should_collide(&player));
+        assert!(CollisionFilter::default().should_collide(&CollisionFilter::default()));
+    }
+
+    #[test]
+    fn groups_override_categories() {
+        let never = CollisionFilter::default().group(-1);
+        let always = CollisionFilter::new(0b01, 0b10).group(2);
+
+        assert!(!never.should_collide(&never));
+        assert!(always.should_collide(&always));
+        assert!(never.should_collide(&CollisionFilter::default()));
+    }
+
+    #[test]
+    fn filtered_bodies_pass_through_each_other() {
+        let mut world = world();
+        let ground = world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let ghost = world.add_body(Body::aabb(-2., 0.5, 1., 1., DEFAULT));
+        let solid = world.add_body(Body::aabb(2., 0.5, 1., 1., DEFAULT));
+
+        world.set_filter(ghost, CollisionFilter::new(0b10, 0b10));
+        world.set_filter(ground, CollisionFilter::new(0b01, 0b01));
+        world.get_body_mut(solid).unwrap().filter = CollisionFilter::new(0b01, 0b01);
+
+        run(&mut world, 60);
+
+        let bodies = world.get_bodies();
+        assert!(bodies[ghost].transform.location.y < -1.);
+        assert!(bodies[solid].transform.location.y > 0.4);
+    }
+
+    #[test]
+    fn changing_a_filter_drops_existing_contacts() {
+        let (mut world, _, body) = sleeping_box();
+
+        world.set_filter(body, CollisionFilter::new(0b10, 0b10));
+        run(&mut world, 30);
+
+        assert!(world.get_bodies()[body].transform.location.y < 0.);
+    }
+}
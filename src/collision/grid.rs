@@ -0,0 +1,159 @@
+//! Uniform grid over the hitboxes of all bodies, used by the broad phase and world queries.<br>
+//! Every body is registered in each cell its hitbox overlaps, so only bodies sharing a cell need to be compared.<br>
+//! Bodies that would cover too many cells (e.g. huge platforms) are kept aside and are candidates for every query.
+
+use std::collections::HashMap;
+
+use crate::{collision::Hitbox, entities::body::Body, math::Vector2};
+
+/// Side length of a cell in meters.
+const CELL_SIZE: f32 = 4.;
+const INV_CELL_SIZE: f32 = 1. / CELL_SIZE;
+
+/// Bodies covering more cells than this are not registered in the cells.
+const MAX_CELLS_PER_BODY: usize = 64;
+
+type Cell = (i32, i32);
+
+#[derive(Clone, Debug, Default)]
+pub struct SpatialGrid {
+    cells: HashMap<Cell, Vec<usize>>,
+    oversized: Vec<usize>,
+    /// Hitboxes in world coordinates, as of the last rebuild.
+    hitboxes: Vec<Hitbox>,
+}
+
+impl SpatialGrid {
+    pub fn rebuild(&mut self, bodies: &[Body]) {
+        self.cells.clear();
+        self.oversized.clear();
+        self.hitboxes.clear();
+
+        for (idx, body) in bodies.iter().enumerate() {
+            let hitbox = &body.hitbox + body.transform.location;
+            let (min, max) = cell_range(&hitbox);
+
+            if cell_count(min, max) > MAX_CELLS_PER_BODY {
+                self.oversized.push(idx);
+            } else {
+                for x in min.0..=max.0 {
+                    for y in min.1..=max.1 {
+                        self.cells.entry((x, y)).or_default().push(idx);
+                    }
+                }
+            }
+
+            self.hitboxes.push(hitbox);
+        }
+    }
+
+    /// Number of bodies the grid was built with.
+    pub fn len(&self) -> usize {
+        self.hitboxes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hitboxes.is_empty()
+    }
+
+    pub fn hitbox(&self, body_idx: usize) -> &Hitbox {
+        &self.hitboxes[body_idx]
+    }
+
+    /// Returns the sorted indices of all bodies whose hitbox overlaps the given one.
+    pub fn query_hitbox(&self, hitbox: &Hitbox) -> Vec<usize> {
+        let mut candidates = self.query_cells(hitbox);
+        candidates.retain(|&idx| self.hitboxes[idx].overlaps(hitbox));
+        candidates
+    }
+
+    /// Returns the sorted indices of all bodies sharing a cell with the hitbox.<br>
+    /// Their hitboxes are not compared, so cheaper checks can run first.
+    pub fn query_cells(&self, hitbox: &Hitbox) -> Vec<usize> {
+        let (min, max) = cell_range(hitbox);
+
+        // visiting more cells than there are bodies is slower than checking every body
+        if cell_count(min, max) > self.len() {
+            return (0..self.len()).collect();
+        }
+
+        let mut candidates = self.oversized.clone();
+
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    candidates.extend_from_slice(cell);
+                }
+            }
+        }
+
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+
+    /// Returns the sorted indices of all bodies in the cells the ray passes through.<br>
+    /// The direction has to be normalized.
+    pub fn query_ray(&self, origin: Vector2, direction: Vector2, max_distance: f32) -> Vec<usize> {
+        let end = origin + direction * max_distance;
+        let (start_cell, end_cell) = (cell_of(origin), cell_of(end));
+        let traversed = start_cell.0.abs_diff(end_cell.0) + start_cell.1.abs_diff(end_cell.1) + 1;
+
+        if !end.x.is_finite() || !end.y.is_finite() || traversed as usize > self.len() {
+            return (0..self.len()).collect();
+        }
+
+        let mut candidates = self.oversized.clone();
+
+        // walks the cells in the order the ray passes through them
+        let mut cell = start_cell;
+        let step = (direction.x.signum() as i32, direction.y.signum() as i32);
+        let (mut next_x, delta_x) = boundary_distance(origin.x, direction.x, cell.0);
+        let (mut next_y, delta_y) = boundary_distance(origin.y, direction.y, cell.1);
+
+        for _ in 0..traversed {
+            if let Some(bodies) = self.cells.get(&cell) {
+                candidates.extend_from_slice(bodies);
+            }
+
+            if next_x < next_y {
+                cell.0 += step.0;
+                next_x += delta_x;
+            } else {
+                cell.1 += step.1;
+                next_y += delta_y;
+            }
+        }
+
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
+
+// --------------------------------- UTILITY FUNCTIONS ---------------------------------
+fn cell_of(p: Vector2) -> Cell {
+    (
+        (p.x * INV_CELL_SIZE).floor() as i32,
+        (p.y * INV_CELL_SIZE).floor() as i32,
+    )
+}
+
+fn cell_range(hitbox: &Hitbox) -> (Cell, Cell) {
+    (cell_of(hitbox.min), cell_of(hitbox.max))
+}
+
+fn cell_count(min: Cell, max: Cell) -> usize {
+    (max.0.abs_diff(min.0) as usize + 1).saturating_mul(max.1.abs_diff(min.1) as usize + 1)
+}
+
+/// Distance along the ray to the first cell boundary on this axis, and between two boundaries.
+fn boundary_distance(origin: f32, direction: f32, cell: i32) -> (f32, f32) {
+    if direction == 0. {
+        return (f32::INFINITY, f32::INFINITY);
+    }
+
+    let boundary = if direction > 0. { cell + 1 } else { cell } as f32 * CELL_SIZE;
+
+    ((boundary - origin) / direction, CELL_SIZE / direction.abs())
+}
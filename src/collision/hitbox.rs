@@ -13,6 +13,19 @@ impl Hitbox {
     pub fn new(min: Vector2, max: Vector2) -> Hitbox {
         Hitbox { min, max }
     }
+
+    /// Touching hitboxes do not overlap.
+    pub fn overlaps(&self, other: &Hitbox) -> bool {
+        if self.min.x >= other.max.x || other.min.x >= self.max.x {
+            return false;
+        }
+
+        if self.min.y >= other.max.y || other.min.y >= self.max.y {
+            return false;
+        }
+
+        true
+    }
 }
 
 impl std::ops::Add<Vector2> for &Hitbox {
@@ -6,6 +6,7 @@ use crate::{
         vector2::{cross, dot, ZERO},
         Vector2,
     },
+    shapes::Shape,
 };
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -15,6 +16,22 @@ pub struct Contact {
     pub diff_to_b: Vector2,
     pub normal_magnitude: f32,
     pub tangent_magnitude: f32,
+    /// Total impulses applied to this contact during the step.
+    pub normal_impulse: f32,
+    pub tangent_impulse: f32,
+    /// Relative normal velocity before solving, negative while approaching.
+    pub relative_velocity: f32,
+    /// Relative normal velocity the contact should separate with after solving.
+    pub bounce_velocity: f32,
+    /// Accumulated split impulse of the position correction.
+    pub pseudo_impulse: f32,
+
+    // soft contact data, only used when sub-stepping
+    /// Separation on detection, minus the separation of the anchors.
+    pub adjusted_separation: f32,
+    /// Impulses of the current substep, warm started into the next one.
+    pub substep_normal_impulse: f32,
+    pub substep_tangent_impulse: f32,
 }
 
 #[derive(Debug)]
@@ -26,16 +43,26 @@ pub struct Manifold {
     pub depth: f32,
     pub contact_count: usize,
     pub inv_contact_count: f32,
-    pub bounce_factor: f32,
+    pub restitution: f32,
     pub friction: f32,
+    /// Target velocity of body b relative to body a along the tangent, e.g. for conveyor belts.
+    pub surface_velocity: f32,
+    /// Maximum counter torque impulse per normal impulse, zero if neither body is a circle.
+    pub rolling_resistance: f32,
+    pub rolling_impulse: f32,
+    /// Disabled manifolds are neither resolved nor reported.
+    pub enabled: bool,
     pub contacts: [Contact; 2],
 }
 
 impl Manifold {
     pub fn new(a: &Body, a_idx: usize, b: &Body, b_idx: usize) -> Manifold {
-        let restitution = (a.material.restitution + b.material.restitution) * 0.5;
-        let bounce_factor = -(1. + restitution);
-        let friction = (a.material.friction + b.material.friction) * 0.5;
+        let (friction, restitution, rolling_resistance) = a.material.combine(&b.material);
+
+        // the tangent is the normal rotated counter clockwise, so clockwise surfaces oppose it
+        let surface_velocity = -(a.surface_velocity + b.surface_velocity);
+
+        let rolling_resistance = rolling_resistance * rolling_radius(a, b);
 
         Manifold {
             a_idx,
@@ -46,16 +73,40 @@ impl Manifold {
             contact_count: 0,
             inv_contact_count: 0.,
             contacts: [Contact::default(); 2],
-            bounce_factor,
+            restitution,
             friction,
+            surface_velocity,
+            rolling_resistance,
+            rolling_impulse: 0.,
+            enabled: true,
         }
     }
+
+    /// Sum of the normal and tangent impulses of all contacts.
+    pub fn total_impulses(&self) -> (f32, f32) {
+        self.contacts
+            .iter()
+            .take(self.contact_count)
+            .fold((0., 0.), |(n, t), c| {
+                (n + c.normal_impulse, t + c.tangent_impulse)
+            })
+    }
 }
 
-const BOUNCE_THRESHHOLD: f32 = 0.0001;
+/// Radius of the larger circle, zero if neither body is one.
+pub(crate) fn rolling_radius(a: &Body, b: &Body) -> f32 {
+    let radius = |body: &Body| match &body.shape {
+        Shape::Circle(c) => c.r,
+        _ => 0.,
+    };
+
+    f32::max(radius(a), radius(b))
+}
 
 impl Manifold {
-    pub fn setup(&mut self, a: &Body, b: &Body, scaled_world_force: Vector2) {
+    /// Prepares the contacts for solving, restitution is only applied to contacts
+    /// approaching faster than the threshold along the normal.
+    pub fn setup(&mut self, a: &Body, b: &Body, restitution_threshold: f32) {
         self.tangent = self.normal.tangent();
         self.inv_contact_count = 1. / self.contact_count as f32;
 
@@ -85,10 +136,74 @@ impl Manifold {
             let mut v_rel = b.transform.velocity + cross(b.transform.angular_velocity, bc);
             v_rel -= a.transform.velocity + cross(a.transform.angular_velocity, ac);
 
-            // do not add bounce factor if only world forces move the body
-            if v_rel.len_squared() < scaled_world_force.len_squared() + BOUNCE_THRESHHOLD {
-                self.bounce_factor = -1.;
-            }
+            contact.relative_velocity = v_rel.dotted(self.normal);
+
+            // resting contacts only approach with the velocity gained this step, they must not bounce
+            contact.bounce_velocity = if -contact.relative_velocity > restitution_threshold {
+                -self.restitution * contact.relative_velocity
+            } else {
+                0.
+            };
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        entities::{
+            body::Body,
+            material::{Material, DEFAULT},
+        },
+        environment::{force::Force, world::World},
+        math::Vector2,
+        test_utils::bounce_height,
+    };
+
+    /// Fastest upward velocity of a bouncy ball thrown down onto ground, without any world force.
+    fn rebound_velocity(world: &mut World, velocity: f32) -> f32 {
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let rubber = Material::new(1., 0.8, 0.9, "rubber");
+        let ball = world.add_body(Body::circle(0., 2., 0.3, rubber));
+        world.get_body_mut(ball).unwrap().transform.velocity = Vector2::new(0., -velocity);
+
+        (0..60)
+            .map(|_| {
+                world.update();
+                world.get_bodies()[ball].transform.velocity.y
+            })
+            .fold(0., f32::max)
+    }
+
+    #[test]
+    fn bounces_do_not_depend_on_world_forces() {
+        let weightless = rebound_velocity(&mut World::new(60., 10.), 6.);
+
+        let mut strong_force = World::new(60., 10.);
+        strong_force.add_force(Force::new(0., -100.));
+        let rubber = Material::new(1., 0.8, 0.9, "rubber");
+        let heavy = bounce_height(&mut strong_force, rubber);
+
+        assert!(weightless > 2., "rebounded with {weightless}");
+        assert!(heavy > 0.8, "bounced up to {heavy}");
+    }
+
+    #[test]
+    fn restitution_threshold_suppresses_slow_bounces() {
+        let mut world = World::new(60., 10.);
+        world
+            .get_solver_settings_mut()
+            .set_restitution_threshold(10.);
+
+        let slow = rebound_velocity(&mut world, 6.);
+        assert!(slow < 0.5, "rebounded with {slow}");
+
+        let mut world = World::new(60., 10.);
+        world
+            .get_solver_settings_mut()
+            .set_restitution_threshold(10.);
+
+        let fast = rebound_velocity(&mut world, 15.);
+        assert!(fast > 5., "rebounded with {fast}");
+    }
+}
@@ -1,15 +1,38 @@
 //! Submodules contain all relevant collision mechanics.
 //! - detection
 //! - resolution
+//! - soft contacts (sub-stepping)
 //! - manifolds
 //! - hitboxes
+//! - spatial grid (broad phase)
+//! - raycasts
+//! - shape casts
+//! - separation (distances)
+//! - filters
+//! - contact modifiers
+//! - solver settings
 
 pub mod detection;
+pub mod filter;
+pub mod grid;
 pub mod hitbox;
 pub mod manifold;
+pub mod modifier;
+pub mod raycast;
 pub mod resolution;
+pub mod separation;
+pub mod settings;
+pub mod shape_cast;
+pub mod soft;
 
 pub use detection::detect_collision;
+pub use filter::CollisionFilter;
 pub use hitbox::*;
 pub use manifold::Manifold;
+pub use modifier::ContactModifier;
+pub use raycast::RaycastHit;
 pub use resolution::{correct_position, resolve_collision};
+pub use separation::Separation;
+pub use settings::SolverSettings;
+pub use shape_cast::ShapeCastHit;
+pub use soft::SubstepSettings;
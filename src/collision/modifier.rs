@@ -0,0 +1,89 @@
+//! Hook to modify contacts after they were detected, but before they get resolved.<br>
+//! Allows per contact friction and restitution, disabling contacts or moving surfaces.
+
+use crate::{collision::Manifold, entities::body::Body};
+
+pub trait ContactModifier {
+    /// Called once per detected contact and step, body a and b match the manifolds a_idx and b_idx.<br>
+    /// Setting manifold.enabled to false skips the contact for this step.
+    fn pre_solve(&mut self, manifold: &mut Manifold, a: &Body, b: &Body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entities::material::DEFAULT, test_utils::world};
+    /// Disables all contacts of the given body.
+    struct PassThrough {
+        body_idx: usize,
+    }
+
+    impl ContactModifier for PassThrough {
+        fn pre_solve(&mut self, manifold: &mut Manifold, _: &Body, _: &Body) {
+            if manifold.a_idx == self.body_idx || manifold.b_idx == self.body_idx {
+                manifold.enabled = false;
+            }
+        }
+    }
+
+    #[test]
+    fn contact_modifiers_can_disable_contacts() {
+        let mut world = world();
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let ghost = world.add_body(Body::aabb(-2., 0.5, 1., 1., DEFAULT));
+        let solid = world.add_body(Body::aabb(2., 0.5, 1., 1., DEFAULT));
+
+        world.set_contact_modifier(PassThrough { body_idx: ghost });
+
+        let mut events = Vec::new();
+
+        for _ in 0..60 {
+            world.update();
+            events.extend(world.drain_contact_events());
+        }
+
+        let bodies = world.get_bodies();
+        assert!(bodies[ghost].transform.location.y < -1.);
+        assert!(bodies[solid].transform.location.y > 0.4);
+
+        // disabled contacts are not reported either
+        assert!(events.iter().all(|e| e.b_idx != ghost));
+        assert!(world.remove_contact_modifier().is_some());
+    }
+
+    /// Makes every contact perfectly bouncy.
+    struct Bouncy;
+
+    impl ContactModifier for Bouncy {
+        fn pre_solve(&mut self, manifold: &mut Manifold, _: &Body, _: &Body) {
+            manifold.restitution = 1.;
+        }
+    }
+
+    #[test]
+    fn contact_modifiers_can_change_restitution() {
+        let mut world = world();
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let ball = world.add_body(Body::circle(0., 2., 0.3, DEFAULT));
+
+        world.set_contact_modifier(Bouncy);
+
+        let mut highest_after_bounce = f32::MIN;
+        let mut bounced = false;
+
+        for _ in 0..90 {
+            world.update();
+            let body = &world.get_bodies()[ball];
+
+            bounced |= body.transform.velocity.y > 0.;
+            if bounced {
+                highest_after_bounce = highest_after_bounce.max(body.transform.location.y);
+            }
+        }
+
+        assert!(
+            highest_after_bounce > 1.7,
+            "bounced up to {highest_after_bounce}"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Ray intersection tests for all shapes, used by the raycast queries of the world.<br>
+//! Rays starting inside a shape do not hit it, so a ray never reports the body it was cast from.
+
+use crate::{
+    entities::body::Body,
+    math::{vector2::ZERO, Vector2},
+    shapes::Shape,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RaycastHit {
+    pub body_idx: usize,
+    pub point: Vector2,
+    /// Surface normal at the hit point, pointing towards the ray origin.
+    pub normal: Vector2,
+    /// Distance the ray travelled before hitting the body.
+    pub distance: f32,
+    /// Share of the max distance the ray travelled before hitting the body, 0 for unlimited rays.
+    pub fraction: f32,
+}
+
+/// Returns the distance along the ray and the surface normal of the first hit.<br>
+/// The direction has to be normalized.
+pub fn raycast_body(
+    body: &Body,
+    origin: Vector2,
+    direction: Vector2,
+    max_distance: f32,
+) -> Option<(f32, Vector2)> {
+    let location = body.transform.location;
+
+    match &body.shape {
+        Shape::Circle(c) => ray_circle(origin, direction, max_distance, location, c.r),
+        Shape::AABB(_) => {
+            let hitbox = &body.hitbox + location;
+            ray_aabb(origin, direction, max_distance, hitbox.min, hitbox.max)
+        }
+        Shape::Polygon(_) => {
+            let (vertices, nr_of_verts) = body.get_moved_vertices();
+            ray_polygon(
+                origin,
+                direction,
+                max_distance,
+                &vertices[0..nr_of_verts],
+                location,
+            )
+        }
+    }
+}
+
+pub fn ray_circle(
+    origin: Vector2,
+    direction: Vector2,
+    max_distance: f32,
+    center: Vector2,
+    r: f32,
+) -> Option<(f32, Vector2)> {
+    let to_origin = origin - center;
+    let c = to_origin.len_squared() - r * r;
+
+    if c <= 0. {
+        return None;
+    }
+
+    // solves |to_origin + t * direction| = r, the direction has length 1
+    let b = to_origin.dotted(direction);
+    let discriminant = b * b - c;
+
+    if b >= 0. || discriminant < 0. {
+        return None;
+    }
+
+    let distance = -b - discriminant.sqrt();
+
+    if distance > max_distance {
+        return None;
+    }
+
+    let normal = (to_origin + direction * distance).normalize_or_zero();
+    Some((distance, normal))
+}
+
+/// Slab test, clips the ray against both pairs of parallel sides.
+pub fn ray_aabb(
+    origin: Vector2,
+    direction: Vector2,
+    max_distance: f32,
+    min: Vector2,
+    max: Vector2,
+) -> Option<(f32, Vector2)> {
+    let mut enter = 0.;
+    let mut exit = max_distance;
+    let mut normal = ZERO;
+
+    for axis in 0..2 {
+        if direction[axis] == 0. {
+            if origin[axis] <= min[axis] || origin[axis] >= max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let inv_direction = 1. / direction[axis];
+        let mut near = (min[axis] - origin[axis]) * inv_direction;
+        let mut far = (max[axis] - origin[axis]) * inv_direction;
+        let mut side = -1.;
+
+        if near > far {
+            (near, far) = (far, near);
+            side = 1.;
+        }
+
+        if near > enter {
+            enter = near;
+            normal = ZERO;
+            normal[axis] = side;
+        }
+
+        exit = f32::min(exit, far);
+
+        if enter > exit {
+            return None;
+        }
+    }
+
+    // the ray started inside
+    if normal == ZERO {
+        return None;
+    }
+
+    Some((enter, normal))
+}
+
+/// Clips the ray against every side of the convex polygon, independent of the winding order.
+pub fn ray_polygon(
+    origin: Vector2,
+    direction: Vector2,
+    max_distance: f32,
+    vertices: &[Vector2],
+    center: Vector2,
+) -> Option<(f32, Vector2)> {
+    let mut enter = 0.;
+    let mut exit = max_distance;
+    let mut normal = ZERO;
+
+    for idx in 0..vertices.len() {
+        let a = vertices[idx];
+        let b = vertices[(idx + 1) % vertices.len()];
+
+        let mut side_normal = (b - a).tangent();
+        if side_normal.dotted(a - center) < 0. {
+            side_normal = ZERO - side_normal;
+        }
+
+        let numerator = side_normal.dotted(a - origin);
+        let denominator = side_normal.dotted(direction);
+
+        if denominator == 0. {
+            if numerator < 0. {
+                return None;
+            }
+            continue;
+        }
+
+        let distance = numerator / denominator;
+
+        if denominator < 0. && distance > enter {
+            enter = distance;
+            normal = side_normal;
+        } else if denominator > 0. && distance < exit {
+            exit = distance;
+        }
+
+        if enter > exit {
+            return None;
+        }
+    }
+
+    // the ray started inside
+    if normal == ZERO {
+        return None;
+    }
+
+    Some((enter, normal.normalize_or_zero()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        collision::CollisionFilter,
+        entities::{body::Body, material::DEFAULT},
+        environment::world::World,
+        math::Vector2,
+        test_utils::sensor,
+    };
+
+    #[test]
+    fn unlimited_rays_report_the_closest_hit_first() {
+        let mut world = World::new(60., 10.);
+        let far = world.add_body(Body::aabb(10., 0., 1., 1., DEFAULT));
+        let near = world.add_body(Body::circle(3., 0., 0.5, DEFAULT));
+        let origin = Vector2::new(0., 0.);
+        let right = Vector2::new(1., 0.);
+
+        let hit = world
+            .raycast(origin, right, f32::INFINITY, CollisionFilter::default())
+            .unwrap();
+        assert_eq!(hit.body_idx, near);
+        assert!((hit.distance - 2.5).abs() < 0.001);
+        assert!((hit.normal - Vector2::new(-1., 0.)).len() < 0.001);
+
+        let hits = world.raycast_all(origin, right, f32::INFINITY, CollisionFilter::default());
+        let order: Vec<usize> = hits.iter().map(|h| h.body_idx).collect();
+        assert_eq!(order, vec![near, far]);
+        assert!((hits[1].point - Vector2::new(9.5, 0.)).len() < 0.001);
+
+        // limited rays still report the share of the distance
+        let hit = world
+            .raycast(origin, right, 5., CollisionFilter::default())
+            .unwrap();
+        assert!((hit.fraction - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn rays_skip_sensors_filtered_bodies_and_their_origin() {
+        let mut world = World::new(60., 10.);
+        let inside = world.add_body(Body::aabb(0., 0., 1., 1., DEFAULT));
+        sensor(&mut world, 2., 0., 1.);
+        let filtered = world.add_body(Body::aabb(4., 0., 1., 1., DEFAULT));
+        let target = world.add_body(Body::aabb(6., 0., 1., 1., DEFAULT));
+
+        world.set_filter(filtered, CollisionFilter::new(0b10, u32::MAX));
+
+        let hits = world.raycast_all(
+            Vector2::new(0., 0.),
+            Vector2::new(1., 0.),
+            10.,
+            CollisionFilter::new(u32::MAX, 0b01),
+        );
+
+        let hit_bodies: Vec<usize> = hits.iter().map(|h| h.body_idx).collect();
+        assert_eq!(hit_bodies, vec![target]);
+        assert!(!hit_bodies.contains(&inside));
+    }
+}
@@ -2,7 +2,7 @@
 //! Needs additional information in the form of a manifold.
 
 use crate::{
-    collision::Manifold,
+    collision::{Manifold, SolverSettings},
     entities::body::Body,
     math::{vector2::cross, Vector2},
 };
@@ -14,8 +14,9 @@ pub fn resolve_collision(m: &mut Manifold, bodies: &mut [Body]) {
 
 fn resolve(m: &mut Manifold, a: &mut Body, b: &mut Body) {
     let mut v_rel;
+    let mut total_jn = 0.;
 
-    for c in m.contacts.into_iter().take(m.contact_count) {
+    for c in m.contacts.iter_mut().take(m.contact_count) {
         // rotational impulse - normal
         v_rel = b.transform.velocity + cross(b.transform.angular_velocity, c.diff_to_b);
         v_rel -= a.transform.velocity + cross(a.transform.angular_velocity, c.diff_to_a);
@@ -23,16 +24,18 @@ fn resolve(m: &mut Manifold, a: &mut Body, b: &mut Body) {
         let v_rel_n = v_rel.dotted(m.normal);
 
         // if normal impulse is in wrong direction -> apply nothing instead
-        let mut jn = c.normal_magnitude * v_rel_n * m.bounce_factor * m.inv_contact_count;
+        let mut jn = c.normal_magnitude * (c.bounce_velocity - v_rel_n) * m.inv_contact_count;
         jn = f32::max(jn, 0.);
 
         apply_impulses(a, b, jn * m.normal, c.diff_to_a, c.diff_to_b);
+        c.normal_impulse += jn;
+        total_jn += jn;
 
         // friction impulse - tangent
         v_rel = b.transform.velocity + cross(b.transform.angular_velocity, c.diff_to_b);
         v_rel -= a.transform.velocity + cross(a.transform.angular_velocity, c.diff_to_a);
 
-        let v_rel_t = v_rel.dotted(m.tangent);
+        let v_rel_t = v_rel.dotted(m.tangent) - m.surface_velocity;
 
         let max_friction = jn * m.friction;
         let mut jt = c.tangent_magnitude * -v_rel_t * m.inv_contact_count;
@@ -40,6 +43,21 @@ fn resolve(m: &mut Manifold, a: &mut Body, b: &mut Body) {
         jt = jt.clamp(-max_friction, max_friction);
 
         apply_impulses(a, b, jt * m.tangent, c.diff_to_a, c.diff_to_b);
+        c.tangent_impulse += jt;
+    }
+
+    // rolling resistance - counter torque, limited by the normal impulse like friction
+    if m.rolling_resistance > 0. {
+        let inverse_inertia = a.inverse_inertia + b.inverse_inertia;
+
+        if inverse_inertia > 0. {
+            let w_rel = b.transform.angular_velocity - a.transform.angular_velocity;
+            let max_rolling = total_jn * m.rolling_resistance;
+            let jr = (-w_rel / inverse_inertia).clamp(-max_rolling, max_rolling);
+
+            a.transform.angular_velocity -= jr * a.inverse_inertia;
+            b.transform.angular_velocity += jr * b.inverse_inertia;
+        }
     }
 }
 
@@ -57,7 +75,11 @@ fn apply_impulses(a: &mut Body, b: &mut Body, impulse: Vector2, ac: Vector2, bc:
 /// Storing the reference would more efficient, but way more complex due to rust.<br>
 /// We would have to annotate lifetimes for the references, which I tried, but failed to do.<br>
 /// For time reasons, i will implement this after I finished my thesis.
-fn collision_bodies(a_idx: usize, b_idx: usize, bodies: &mut [Body]) -> (&mut Body, &mut Body) {
+pub(crate) fn collision_bodies(
+    a_idx: usize,
+    b_idx: usize,
+    bodies: &mut [Body],
+) -> (&mut Body, &mut Body) {
     let mut ref_iter = bodies.iter_mut().enumerate();
 
     let (a, b);
@@ -85,15 +107,132 @@ fn collision_bodies(a_idx: usize, b_idx: usize, bodies: &mut [Body]) -> (&mut Bo
     (a, b)
 }
 
-const CORRECTION_FACTOR: f32 = 0.6;
-const ALLOWED_INTERSECTION: f32 = 0.005;
-
-pub fn correct_position(m: &Manifold, bodies: &mut [Body]) {
+/// Pushes intersecting bodies apart via split impulses.<br>
+/// They only change the pseudo velocities, which move the bodies for a single step and
+/// get discarded afterwards, so position correction never adds energy to the real velocities.
+pub fn correct_position(
+    m: &mut Manifold,
+    bodies: &mut [Body],
+    settings: &SolverSettings,
+    inv_dt: f32,
+) {
     let (a, b) = collision_bodies(m.a_idx, m.b_idx, bodies);
-    let correction = f32::max(m.depth - ALLOWED_INTERSECTION, 0.)
-        / (a.inverse_mass + b.inverse_mass)
-        * CORRECTION_FACTOR
-        * m.normal;
-    a.transform.location -= correction * a.inverse_mass;
-    b.transform.location += correction * b.inverse_mass;
+    let bias = f32::max(m.depth - settings.get_allowed_intersection(), 0.)
+        * settings.get_correction_factor()
+        * inv_dt;
+
+    for c in m.contacts.iter_mut().take(m.contact_count) {
+        let mut v_rel = b.pseudo_velocity + cross(b.pseudo_angular_velocity, c.diff_to_b);
+        v_rel -= a.pseudo_velocity + cross(a.pseudo_angular_velocity, c.diff_to_a);
+
+        let v_rel_n = v_rel.dotted(m.normal);
+
+        // accumulated, so the bodies are never pulled together
+        let jp = c.normal_magnitude * (bias - v_rel_n) * m.inv_contact_count;
+        let previous = c.pseudo_impulse;
+        c.pseudo_impulse = f32::max(previous + jp, 0.);
+        let impulse = (c.pseudo_impulse - previous) * m.normal;
+
+        a.pseudo_velocity -= impulse * a.inverse_mass;
+        a.pseudo_angular_velocity -= c.diff_to_a.crossed(impulse) * a.inverse_inertia;
+
+        b.pseudo_velocity += impulse * b.inverse_mass;
+        b.pseudo_angular_velocity += c.diff_to_b.crossed(impulse) * b.inverse_inertia;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        entities::{
+            body::Body,
+            material::{Material, DEFAULT},
+        },
+        environment::world::World,
+        math::Vector2,
+        test_utils::{run, world},
+    };
+
+    #[test]
+    fn position_correction_does_not_add_velocity() {
+        let mut world = World::new(60., 10.);
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let body = world.add_body(Body::aabb(0., 0.2, 1., 1., DEFAULT));
+
+        run(&mut world, 60);
+
+        let body = &world.get_bodies()[body];
+        let allowed_intersection = world.get_solver_settings().get_allowed_intersection();
+
+        assert!(body.transform.location.y > 0.5 - allowed_intersection * 2.);
+        assert!(body.transform.location.y < 0.51);
+        assert!(body.transform.velocity.len() < 0.01);
+    }
+
+    #[test]
+    fn resting_stacks_stay_in_place_with_enough_iterations() {
+        let mut world = world();
+        world.set_sleeping_enabled(false);
+        world.set_collision_precision(10);
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let stack: Vec<usize> = (0..5)
+            .map(|idx| world.add_body(Body::aabb(0., 0.5 + idx as f32, 1., 1., DEFAULT)))
+            .collect();
+
+        run(&mut world, 300);
+
+        let top = &world.get_bodies()[stack[4]];
+        assert!(
+            (top.transform.location.y - 4.5).abs() < 0.05,
+            "{}",
+            top.transform.location
+        );
+    }
+
+    #[test]
+    fn conveyor_belts_drag_bodies_along() {
+        let mut world = world();
+        let mut belt = Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT);
+        belt.surface_velocity = 2.;
+        world.add_body(belt);
+
+        let body = world.add_body(Body::aabb(0., 0.5, 1., 1., DEFAULT));
+
+        run(&mut world, 120);
+
+        let velocity = world.get_bodies()[body].transform.velocity;
+        assert!((velocity.x - 2.).abs() < 0.1, "moves with {velocity}");
+    }
+
+    /// Distance a ball rolls on flat ground, after being pushed with 5 m/s.
+    fn rolling_distance(material: Material) -> f32 {
+        let mut world = world();
+        world.set_sleeping_enabled(false);
+        world.add_body(Body::platform_rectangle_aabb(
+            0.,
+            -0.5,
+            200.,
+            1.,
+            material.clone(),
+        ));
+
+        let ball = world.add_body(Body::circle(0., 0.5, 0.5, material));
+        world.get_body_mut(ball).unwrap().transform.velocity = Vector2::new(5., 0.);
+
+        run(&mut world, 180);
+
+        world.get_bodies()[ball].transform.location.x
+    }
+
+    #[test]
+    fn rolling_resistance_slows_down_circles() {
+        let free = rolling_distance(DEFAULT);
+        let resisting = rolling_distance(DEFAULT.rolling_resistance(0.2));
+
+        assert!(free > 10., "rolled {free}");
+        assert!(
+            resisting < free * 0.5,
+            "rolled {resisting} instead of {free}"
+        );
+    }
 }
@@ -0,0 +1,169 @@
+//! Separation of two bodies, even if they do not touch.<br>
+//! Every shape is treated as a convex set of points with a radius, circles being a single point.
+//! The closest points of two such sets lie on a vertex of one and a side of the other.<br>
+//! Overlapping bodies are resolved by the narrow phase, their distance is the negative penetration depth.
+
+use crate::{
+    collision::{detect_collision, detection::project_onto_line, SolverSettings},
+    entities::body::{Body, Vertices, MAX_VERTICE_COUNT},
+    math::{vector2::ZERO, Vector2},
+    shapes::Shape,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Separation {
+    /// Zero if the bodies touch, negative if they overlap.
+    pub distance: f32,
+    /// Closest point on the surface of body a, the deepest contact point if they overlap.
+    pub point_a: Vector2,
+    /// Closest point on the surface of body b, the deepest contact point if they overlap.
+    pub point_b: Vector2,
+    /// Direction from body a to body b.
+    pub normal: Vector2,
+}
+
+pub fn separation(a: &Body, b: &Body, settings: &SolverSettings) -> Separation {
+    let hitbox_a = &a.hitbox + a.transform.location;
+    let hitbox_b = &b.hitbox + b.transform.location;
+
+    if hitbox_a.overlaps(&hitbox_b) {
+        // the indices only tell which body ended up as a in the manifold
+        if let Some(m) = detect_collision(a, 0, b, 1, settings) {
+            let contacts = &m.contacts[0..m.contact_count];
+            let point = contacts.iter().fold(ZERO, |sum, c| {
+                sum + c.location * (1. / contacts.len() as f32)
+            });
+            let normal = if m.a_idx == 0 {
+                m.normal
+            } else {
+                ZERO - m.normal
+            };
+
+            return Separation {
+                distance: -m.depth,
+                point_a: point,
+                point_b: point,
+                normal,
+            };
+        }
+    }
+
+    let (core_a, count_a, radius_a) = core(a);
+    let (core_b, count_b, radius_b) = core(b);
+    let (core_a, core_b) = (&core_a[0..count_a], &core_b[0..count_b]);
+
+    let (mut closest_a, mut closest_b) = (core_a[0], core_b[0]);
+
+    for (p, on_b) in closest_on_sides(core_a, core_b)
+        .into_iter()
+        .chain(closest_on_sides(core_b, core_a).map(|(p, on_a)| (on_a, p)))
+    {
+        if Vector2::distance_squared(p, on_b) < Vector2::distance_squared(closest_a, closest_b) {
+            (closest_a, closest_b) = (p, on_b);
+        }
+    }
+
+    let core_distance = Vector2::distance(closest_a, closest_b);
+    let normal = (closest_b - closest_a).normalize_or_zero();
+
+    Separation {
+        distance: f32::max(core_distance - radius_a - radius_b, 0.),
+        point_a: closest_a + normal * radius_a,
+        point_b: closest_b - normal * radius_b,
+        normal,
+    }
+}
+
+/// Vertices in world coordinates and the radius around them.
+fn core(body: &Body) -> (Vertices, usize, f32) {
+    match &body.shape {
+        Shape::Circle(c) => {
+            let mut vertices: Vertices = [ZERO; MAX_VERTICE_COUNT];
+            vertices[0] = body.transform.location;
+            (vertices, 1, c.r)
+        }
+        _ => {
+            let (vertices, nr_of_verts) = body.get_moved_vertices();
+            (vertices, nr_of_verts, 0.)
+        }
+    }
+}
+
+/// Closest pair of every point and the sides of the other shape.
+fn closest_on_sides(points: &[Vector2], shape: &[Vector2]) -> Option<(Vector2, Vector2)> {
+    points
+        .iter()
+        .flat_map(|&p| {
+            (0..shape.len()).map(move |idx| {
+                let (side_start, side_end) = (shape[idx], shape[(idx + 1) % shape.len()]);
+
+                // circles have a single point instead of sides
+                if side_start == side_end {
+                    return (p, side_start);
+                }
+
+                (p, project_onto_line(side_start, side_end, p))
+            })
+        })
+        .min_by(|(p1, q1), (p2, q2)| {
+            Vector2::distance_squared(*p1, *q1).total_cmp(&Vector2::distance_squared(*p2, *q2))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        entities::{body::Body, material::DEFAULT},
+        environment::world::World,
+        math::Vector2,
+    };
+    use std::f32::consts::PI;
+
+    #[test]
+    fn distance_reports_closest_points_of_every_shape() {
+        let mut world = World::new(60., 10.);
+        let ball = world.add_body(Body::circle(0., 0., 0.5, DEFAULT));
+        let other_ball = world.add_body(Body::circle(3., 0., 1., DEFAULT));
+        let square = world.add_body(Body::aabb(3., 3., 1., 1., DEFAULT));
+        let mut diamond = Body::obb(0., 3., 1., 1., DEFAULT);
+        diamond.rotate_fixed_angle(PI / 4.);
+        let diamond = world.add_body(diamond);
+
+        let between_balls = world.distance(ball, other_ball).unwrap();
+        assert!((between_balls.distance - 1.5).abs() < 0.001);
+        assert!((between_balls.point_a - Vector2::new(0.5, 0.)).len() < 0.001);
+        assert!((between_balls.point_b - Vector2::new(2., 0.)).len() < 0.001);
+        assert!((between_balls.normal - Vector2::new(1., 0.)).len() < 0.001);
+
+        // the corner of the diamond points straight down at the ball
+        let to_diamond = world.distance(ball, diamond).unwrap();
+        let corner = 3. - 0.5_f32.sqrt();
+        assert!((to_diamond.distance - (corner - 0.5)).abs() < 0.001);
+        assert!((to_diamond.point_b - Vector2::new(0., corner)).len() < 0.001);
+
+        let to_square = world.distance(other_ball, square).unwrap();
+        assert!((to_square.distance - 1.5).abs() < 0.001);
+        assert!((to_square.point_b - Vector2::new(3., 2.5)).len() < 0.001);
+
+        assert!(world.distance(ball, 10).is_none());
+    }
+
+    #[test]
+    fn overlapping_bodies_have_a_negative_distance() {
+        let mut world = World::new(60., 10.);
+        let a = world.add_body(Body::aabb(0., 0., 2., 2., DEFAULT));
+        let b = world.add_body(Body::aabb(1.5, 0., 2., 2., DEFAULT));
+        let c = world.add_body(Body::aabb(3.5, 0., 2., 2., DEFAULT));
+
+        let overlap = world.distance(a, b).unwrap();
+        assert!((overlap.distance + 0.5).abs() < 0.001);
+        assert!((overlap.normal - Vector2::new(1., 0.)).len() < 0.001);
+
+        // the normal keeps pointing from a to b if the arguments are swapped
+        let swapped = world.distance(b, a).unwrap();
+        assert!((swapped.normal - Vector2::new(-1., 0.)).len() < 0.001);
+
+        let touching = world.distance(b, c).unwrap();
+        assert!(touching.distance.abs() < 0.001);
+    }
+}
@@ -0,0 +1,319 @@
+//! Tuning parameters of the collision pipeline, owned by the world.<br>
+//! All setters validate their input, invalid values get clamped to the closest valid one.<br>
+//! The presets trade accuracy for speed:
+//! - fast: single iteration, aggressive position correction
+//! - accurate: sub-stepping with soft contacts, 8 substeps per step
+
+use crate::collision::SubstepSettings;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolverSettings {
+    precision: usize,
+    min_precision: usize,
+    max_precision: usize,
+    correction_factor: f32,
+    allowed_intersection: f32,
+    restitution_threshold: f32,
+    impulse_threshold: f32,
+    contact_tolerance: f32,
+    one_way_slop: f32,
+    joint_correction_factor: f32,
+    joint_position_iterations: usize,
+    warm_start_distance: f32,
+    warm_start_alignment: f32,
+    substepping: Option<SubstepSettings>,
+}
+
+impl Default for SolverSettings {
+    fn default() -> Self {
+        SolverSettings {
+            precision: 1,
+            min_precision: 10,
+            max_precision: 100,
+            correction_factor: 0.6,
+            allowed_intersection: 0.005,
+            restitution_threshold: 1.,
+            impulse_threshold: 0.0008831703,
+            contact_tolerance: 0.0001,
+            one_way_slop: 0.01,
+            joint_correction_factor: 0.8,
+            joint_position_iterations: 8,
+            warm_start_distance: 0.05,
+            warm_start_alignment: 0.9,
+            substepping: None,
+        }
+    }
+}
+
+// --------------------------------- PRESETS ---------------------------------
+impl SolverSettings {
+    pub fn fast() -> SolverSettings {
+        SolverSettings {
+            precision: 1,
+            min_precision: 1,
+            correction_factor: 0.8,
+            allowed_intersection: 0.01,
+            ..Default::default()
+        }
+    }
+
+    pub fn accurate() -> SolverSettings {
+        SolverSettings {
+            substepping: Some(SubstepSettings::new(8)),
+            ..Default::default()
+        }
+    }
+}
+
+// --------------------------------- GETTERS ---------------------------------
+impl SolverSettings {
+    pub fn get_precision(&self) -> usize {
+        self.precision
+    }
+
+    pub fn get_precision_range(&self) -> (usize, usize) {
+        (self.min_precision, self.max_precision)
+    }
+
+    pub fn get_correction_factor(&self) -> f32 {
+        self.correction_factor
+    }
+
+    pub fn get_allowed_intersection(&self) -> f32 {
+        self.allowed_intersection
+    }
+
+    pub fn get_restitution_threshold(&self) -> f32 {
+        self.restitution_threshold
+    }
+
+    pub fn get_impulse_threshold(&self) -> f32 {
+        self.impulse_threshold
+    }
+
+    pub fn get_contact_tolerance(&self) -> f32 {
+        self.contact_tolerance
+    }
+
+    pub fn get_one_way_slop(&self) -> f32 {
+        self.one_way_slop
+    }
+
+    pub fn get_joint_correction_factor(&self) -> f32 {
+        self.joint_correction_factor
+    }
+
+    pub fn get_joint_position_iterations(&self) -> usize {
+        self.joint_position_iterations
+    }
+
+    pub fn get_warm_start_distance(&self) -> f32 {
+        self.warm_start_distance
+    }
+
+    pub fn get_warm_start_alignment(&self) -> f32 {
+        self.warm_start_alignment
+    }
+
+    pub fn get_substepping(&self) -> Option<SubstepSettings> {
+        self.substepping
+    }
+}
+
+// --------------------------------- SETTERS ---------------------------------
+impl SolverSettings {
+    /// Defines how many iterations the collision pipeline should execute.<br>
+    /// This is a tradeoff of accuracy and computiation complexity.<br>
+    /// Gets clamped to the precision range.
+    pub fn set_precision(&mut self, precision: usize) {
+        self.precision = precision.clamp(self.min_precision, self.max_precision);
+    }
+
+    /// The range the precision gets clamped to, at least one iteration is always executed.
+    pub fn set_precision_range(&mut self, min_precision: usize, max_precision: usize) {
+        self.min_precision = min_precision.max(1);
+        self.max_precision = max_precision.max(self.min_precision);
+        self.set_precision(self.precision);
+    }
+
+    /// Defines which share of an intersection gets corrected each step, clamped to 0-1.<br>
+    /// Higher values separate bodies faster, but may cause jitter.
+    pub fn set_correction_factor(&mut self, correction_factor: f32) {
+        self.correction_factor = correction_factor.clamp(0., 1.);
+    }
+
+    /// Defines how deep bodies may intersect without getting corrected.<br>
+    /// A small intersection keeps resting contacts alive between steps.
+    pub fn set_allowed_intersection(&mut self, allowed_intersection: f32) {
+        self.allowed_intersection = f32::max(allowed_intersection, 0.);
+    }
+
+    /// Speed a contact needs to approach with along its normal to bounce at all.<br>
+    /// Slower contacts are treated as resting, regardless of which forces move the bodies.
+    pub fn set_restitution_threshold(&mut self, restitution_threshold: f32) {
+        self.restitution_threshold = f32::max(restitution_threshold, 0.);
+    }
+
+    /// Squared length impulses need to exceed to get applied via World::apply_impulse.
+    pub fn set_impulse_threshold(&mut self, impulse_threshold: f32) {
+        self.impulse_threshold = f32::max(impulse_threshold, 0.);
+    }
+
+    /// Distances within this tolerance count as equal when generating contact points.
+    pub fn set_contact_tolerance(&mut self, contact_tolerance: f32) {
+        self.contact_tolerance = f32::max(contact_tolerance, f32::EPSILON);
+    }
+
+    /// Depth one-way bodies may already be intersected at the start of a step and still block,
+    /// deeper contacts started inside and get passed through.
+    pub fn set_one_way_slop(&mut self, one_way_slop: f32) {
+        self.one_way_slop = f32::max(one_way_slop, 0.);
+    }
+
+    /// Defines which share of the positional error of joints gets corrected each step, clamped to 0-1.
+    pub fn set_joint_correction_factor(&mut self, joint_correction_factor: f32) {
+        self.joint_correction_factor = joint_correction_factor.clamp(0., 1.);
+    }
+
+    /// Passes of the joint position correction each step (or substep), zero disables it.
+    pub fn set_joint_position_iterations(&mut self, joint_position_iterations: usize) {
+        self.joint_position_iterations = joint_position_iterations;
+    }
+
+    /// Contacts that moved further than this between two steps do not reuse their impulses.<br>
+    /// Only affects sub-stepping, the iterative solver starts every step from scratch.
+    pub fn set_warm_start_distance(&mut self, warm_start_distance: f32) {
+        self.warm_start_distance = f32::max(warm_start_distance, 0.);
+    }
+
+    /// Minimum cosine between the contact normals of two steps to reuse their impulses, clamped to -1-1.
+    pub fn set_warm_start_alignment(&mut self, warm_start_alignment: f32) {
+        self.warm_start_alignment = warm_start_alignment.clamp(-1., 1.);
+    }
+
+    /// Enables sub-stepping with soft contacts, None switches back to the iterative solver.<br>
+    /// The precision and position correction settings only affect the iterative solver.
+    pub fn set_substepping(&mut self, substepping: Option<SubstepSettings>) {
+        self.substepping = substepping.map(|mut s| {
+            s.substeps = s.substeps.max(1);
+            s.contact_frequency = f32::max(s.contact_frequency, 0.);
+            s.contact_damping = f32::max(s.contact_damping, 0.);
+            s.max_push_velocity = f32::max(s.max_push_velocity, 0.);
+            s
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{body::Body, material::DEFAULT},
+        environment::world::World,
+        math::Vector2,
+    };
+
+    #[test]
+    fn precision_stays_in_range() {
+        let mut settings = SolverSettings::default();
+
+        settings.set_precision(1000);
+        assert_eq!(settings.get_precision(), 100);
+
+        settings.set_precision(2);
+        assert_eq!(settings.get_precision(), 10);
+
+        settings.set_precision_range(0, 5);
+        assert_eq!(settings.get_precision_range(), (1, 5));
+        assert_eq!(settings.get_precision(), 5);
+
+        // a maximum below the minimum collapses the range
+        settings.set_precision_range(8, 3);
+        assert_eq!(settings.get_precision_range(), (8, 8));
+        assert_eq!(settings.get_precision(), 8);
+    }
+
+    #[test]
+    fn invalid_values_get_clamped() {
+        let mut settings = SolverSettings::default();
+
+        settings.set_correction_factor(1.5);
+        settings.set_allowed_intersection(-1.);
+        settings.set_restitution_threshold(-1.);
+        settings.set_impulse_threshold(-1.);
+        settings.set_contact_tolerance(0.);
+        settings.set_one_way_slop(-1.);
+        settings.set_joint_correction_factor(2.);
+        settings.set_warm_start_distance(-1.);
+        settings.set_warm_start_alignment(3.);
+
+        assert_eq!(settings.get_correction_factor(), 1.);
+        assert_eq!(settings.get_allowed_intersection(), 0.);
+        assert_eq!(settings.get_restitution_threshold(), 0.);
+        assert_eq!(settings.get_impulse_threshold(), 0.);
+        assert_eq!(settings.get_contact_tolerance(), f32::EPSILON);
+        assert_eq!(settings.get_one_way_slop(), 0.);
+        assert_eq!(settings.get_joint_correction_factor(), 1.);
+        assert_eq!(settings.get_warm_start_distance(), 0.);
+        assert_eq!(settings.get_warm_start_alignment(), 1.);
+
+        settings.set_correction_factor(-0.5);
+        settings.set_joint_correction_factor(-0.5);
+        settings.set_warm_start_alignment(-3.);
+        assert_eq!(settings.get_correction_factor(), 0.);
+        assert_eq!(settings.get_joint_correction_factor(), 0.);
+        assert_eq!(settings.get_warm_start_alignment(), -1.);
+    }
+
+    #[test]
+    fn invalid_substeps_get_clamped() {
+        let mut settings = SolverSettings::default();
+
+        settings.set_substepping(Some(SubstepSettings {
+            substeps: 0,
+            contact_frequency: -30.,
+            contact_damping: -1.,
+            max_push_velocity: -3.,
+            ..Default::default()
+        }));
+
+        let substepping = settings.get_substepping().unwrap();
+        assert_eq!(substepping.substeps, 1);
+        assert_eq!(substepping.contact_frequency, 0.);
+        assert_eq!(substepping.contact_damping, 0.);
+        assert_eq!(substepping.max_push_velocity, 0.);
+
+        settings.set_substepping(None);
+        assert!(settings.get_substepping().is_none());
+    }
+
+    #[test]
+    fn presets_trade_accuracy_for_speed() {
+        let fast = SolverSettings::fast();
+        let accurate = SolverSettings::accurate();
+
+        assert_eq!(fast.get_precision(), 1);
+        assert!(fast.get_substepping().is_none());
+        assert!(fast.get_correction_factor() > SolverSettings::default().get_correction_factor());
+
+        assert_eq!(accurate.get_substepping().unwrap().substeps, 8);
+    }
+
+    #[test]
+    fn world_applies_impulses_above_its_threshold() {
+        let mut world = World::new(60., 10.);
+        let body = world.add_body(Body::circle(0., 0., 0.5, DEFAULT));
+        let impulse = Vector2::new(0.1, 0.);
+
+        world.get_solver_settings_mut().set_impulse_threshold(0.02);
+        world.apply_impulse(body, impulse);
+        assert_eq!(
+            world.get_bodies()[body].transform.velocity,
+            Vector2::default()
+        );
+
+        world.get_solver_settings_mut().set_impulse_threshold(0.);
+        world.apply_impulse(body, impulse);
+        assert_eq!(world.get_bodies()[body].transform.velocity, impulse);
+    }
+}
@@ -0,0 +1,298 @@
+//! Swept queries, moves a shape along a translation and finds the first body it hits.<br>
+//! Overlaps are tested with the regular narrow phase, first at steps smaller than both shapes,
+//! then the time of impact gets refined by bisection.<br>
+//! Only the part of the translation where the hitboxes can overlap is sampled.
+
+use crate::{
+    collision::{detect_collision, Hitbox, Manifold, SolverSettings},
+    entities::body::Body,
+    math::{vector2::ZERO, Vector2},
+    shapes::Shape,
+};
+
+/// Bisection steps used to refine the time of impact.
+const TOI_ITERATIONS: usize = 20;
+
+/// Index the probe uses in manifolds, it is never part of the world.
+const PROBE_IDX: usize = usize::MAX;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapeCastHit {
+    pub body_idx: usize,
+    /// Share of the translation the shape moved before touching the body, 0 if it started inside.
+    pub toi: f32,
+    pub point: Vector2,
+    /// Surface normal of the hit body, pointing towards the cast shape.
+    pub normal: Vector2,
+}
+
+/// Returns the time of impact, contact point and normal of the probe moving into the target.<br>
+/// The probe gets moved during the cast, but ends up at its start again.
+pub fn shape_cast_body(
+    probe: &mut Body,
+    translation: Vector2,
+    target: &Body,
+    settings: &SolverSettings,
+) -> Option<(f32, Vector2, Vector2)> {
+    let start = probe.transform.location;
+    let result = cast(probe, start, translation, target, settings);
+
+    probe.transform.location = start;
+    result
+}
+
+/// Hitbox covering the whole translation of the body.
+pub fn swept_hitbox(body: &Body, translation: Vector2) -> Hitbox {
+    let hitbox = &body.hitbox + body.transform.location;
+    let moved = &hitbox + translation;
+
+    Hitbox::new(
+        Vector2::min(hitbox.min, moved.min),
+        Vector2::max(hitbox.max, moved.max),
+    )
+}
+
+fn cast(
+    probe: &mut Body,
+    start: Vector2,
+    translation: Vector2,
+    target: &Body,
+    settings: &SolverSettings,
+) -> Option<(f32, Vector2, Vector2)> {
+    let (enter, exit) = hitbox_interval(probe, translation, target)?;
+
+    let overlap = |probe: &mut Body, t: f32| {
+        probe.transform.location = start + translation * t;
+        detect_collision(probe, PROBE_IDX, target, 0, settings)
+    };
+
+    let length = translation.len();
+    let step = if length > 0. {
+        0.5 * f32::min(thickness(probe), thickness(target)) / length
+    } else {
+        1.
+    };
+
+    // first sample that overlaps, lower stays the last one that does not
+    let mut lower = enter;
+    let mut upper = enter;
+    let mut manifold = overlap(probe, enter);
+
+    while manifold.is_none() {
+        if upper >= exit {
+            return None;
+        }
+
+        lower = upper;
+        upper = f32::min(upper + step, exit);
+        manifold = overlap(probe, upper);
+    }
+
+    if upper > 0. {
+        for _ in 0..TOI_ITERATIONS {
+            let t = 0.5 * (lower + upper);
+
+            if let Some(m) = overlap(probe, t) {
+                upper = t;
+                manifold = Some(m);
+            } else {
+                lower = t;
+            }
+        }
+    }
+
+    let m = manifold?;
+    Some((upper, contact_point(&m), hit_normal(&m)))
+}
+
+/// Range of the translation in which the hitboxes overlap, as shares of the translation.
+fn hitbox_interval(probe: &Body, translation: Vector2, target: &Body) -> Option<(f32, f32)> {
+    let probe_hitbox = &probe.hitbox + probe.transform.location;
+    let target_hitbox = &target.hitbox + target.transform.location;
+
+    let mut enter: f32 = 0.;
+    let mut exit: f32 = 1.;
+
+    for axis in 0..2 {
+        // the probe hitbox touches the target at these offsets
+        let min = target_hitbox.min[axis] - probe_hitbox.max[axis];
+        let max = target_hitbox.max[axis] - probe_hitbox.min[axis];
+
+        if translation[axis] == 0. {
+            if min >= 0. || max <= 0. {
+                return None;
+            }
+            continue;
+        }
+
+        let (near, far) = (min / translation[axis], max / translation[axis]);
+        enter = enter.max(near.min(far));
+        exit = exit.min(near.max(far));
+    }
+
+    if enter > exit {
+        return None;
+    }
+
+    Some((enter, exit))
+}
+
+/// Smallest width of the body, moving less than this never skips over it.
+fn thickness(body: &Body) -> f32 {
+    match &body.shape {
+        Shape::Circle(c) => 2. * c.r,
+        Shape::AABB(_) => {
+            let size = body.hitbox.max - body.hitbox.min;
+            f32::min(size.x, size.y)
+        }
+        Shape::Polygon(_) => {
+            let (vertices, nr_of_verts) = body.get_vertices();
+            let vertices = &vertices[0..nr_of_verts];
+            let mut thickness = f32::MAX;
+
+            for idx in 0..nr_of_verts {
+                let a = vertices[idx];
+                let axis = (vertices[(idx + 1) % nr_of_verts] - a)
+                    .tangent()
+                    .normalize_or_zero();
+
+                let width = vertices
+                    .iter()
+                    .map(|&v| (v - a).dotted(axis).abs())
+                    .fold(0., f32::max);
+
+                thickness = f32::min(thickness, width);
+            }
+
+            thickness
+        }
+    }
+}
+
+fn contact_point(m: &Manifold) -> Vector2 {
+    let contacts = &m.contacts[0..m.contact_count];
+    let sum = contacts.iter().fold(ZERO, |sum, c| sum + c.location);
+
+    sum * (1. / contacts.len() as f32)
+}
+
+/// Manifold normals point from body a to body b, the probe might be either.
+fn hit_normal(m: &Manifold) -> Vector2 {
+    if m.a_idx == PROBE_IDX {
+        ZERO - m.normal
+    } else {
+        m.normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        collision::CollisionFilter,
+        entities::{body::Body, material::DEFAULT},
+        environment::world::World,
+        math::Vector2,
+        shapes::{aabb::AABB, circle::Circle, polygon::Polygon, Shape},
+        test_utils::sensor,
+    };
+    use std::f32::consts::PI;
+
+    #[test]
+    fn shape_casts_stop_at_the_first_body() {
+        let mut world = World::new(60., 10.);
+        let ground = world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        world.add_body(Body::aabb(0., -5., 1., 1., DEFAULT));
+        sensor(&mut world, 0., 2., 1.);
+
+        let ball = Shape::Circle(Circle::new(0.5));
+        let down = Vector2::new(0., -10.);
+        let start = Vector2::new(0., 5.);
+
+        let hit = world
+            .shape_cast(&ball, start, 0., down, CollisionFilter::default())
+            .unwrap();
+
+        // the ball touches the ground after moving 4.5 of the 10 units
+        assert_eq!(hit.body_idx, ground);
+        assert!((hit.toi - 0.45).abs() < 0.01, "toi {}", hit.toi);
+        assert!((hit.normal - Vector2::new(0., 1.)).len() < 0.01);
+        assert!(hit.point.y.abs() < 0.01, "{}", hit.point);
+
+        let short = Vector2::new(0., -4.);
+        assert!(world
+            .shape_cast(&ball, start, 0., short, CollisionFilter::default())
+            .is_none());
+    }
+
+    #[test]
+    fn rotated_shape_casts_hit_with_their_corner() {
+        let mut world = World::new(60., 10.);
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+
+        let corners = AABB::generate_corners(1., 1.).to_vec();
+        let square = Shape::Polygon(Polygon::new(corners).unwrap());
+        let down = Vector2::new(0., -5.);
+        let start = Vector2::new(0., 3.);
+
+        let flat = world
+            .shape_cast(&square, start, 0., down, CollisionFilter::default())
+            .unwrap();
+        let tilted = world
+            .shape_cast(&square, start, PI / 4., down, CollisionFilter::default())
+            .unwrap();
+
+        // a square standing on its corner reaches sqrt(0.5) below its center
+        assert!((flat.toi - 0.5).abs() < 0.01, "toi {}", flat.toi);
+        let expected = (3. - 0.5_f32.sqrt()) / 5.;
+        assert!((tilted.toi - expected).abs() < 0.01, "toi {}", tilted.toi);
+    }
+
+    #[test]
+    fn shape_casts_starting_inside_hit_immediately() {
+        let mut world = World::new(60., 10.);
+        let wall = world.add_body(Body::platform_rectangle_aabb(0., 0., 2., 2., DEFAULT));
+
+        let hit = world
+            .shape_cast(
+                &Shape::Circle(Circle::new(0.25)),
+                Vector2::new(0.5, 0.),
+                0.,
+                Vector2::new(5., 0.),
+                CollisionFilter::default(),
+            )
+            .unwrap();
+
+        assert_eq!(hit.body_idx, wall);
+        assert_eq!(hit.toi, 0.);
+    }
+
+    #[test]
+    fn long_shape_casts_do_not_skip_thin_bodies() {
+        let mut world = World::new(60., 10.);
+        // a thin diagonal wall, its hitbox covers the whole cast
+        let wall = world.add_body(Body::platform_rectangle_obb(
+            500.,
+            500.,
+            1500.,
+            0.05,
+            -PI / 4.,
+            DEFAULT,
+        ));
+
+        let hit = world
+            .shape_cast(
+                &Shape::Circle(Circle::new(0.05)),
+                Vector2::new(-0.5, -0.5),
+                0.,
+                Vector2::new(1000., 1000.),
+                CollisionFilter::default(),
+            )
+            .unwrap();
+
+        // the ball touches the wall 0.075 before its center line
+        let length = 1000. * 2_f32.sqrt();
+        let expected = (500.5 * 2_f32.sqrt() - 0.075) / length;
+        assert_eq!(hit.body_idx, wall);
+        assert!((hit.toi - expected).abs() < 0.0001, "toi {}", hit.toi);
+    }
+}
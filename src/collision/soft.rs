@@ -0,0 +1,389 @@
+//! Soft contact solver, used when the world is sub-stepping.<br>
+//! Contacts are detected once per step, but solved in every substep as soft constraints,
+//! which behave like a damped spring with the given frequency and damping ratio.<br>
+//! Each substep first solves with bias to push bodies apart, integrates the positions
+//! and then relaxes the velocities without bias, so the push does not add energy.<br>
+//! Restitution is applied once after all substeps.
+
+use crate::{
+    collision::{manifold::Contact, resolution::collision_bodies, Manifold, SolverSettings},
+    entities::body::Body,
+    math::{vector2::cross, Vector2},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SubstepSettings {
+    pub substeps: usize,
+    /// Stiffness of the contacts in Hz, clamped to a quarter of the substep rate.
+    pub contact_frequency: f32,
+    /// Damping ratio of the contacts, 1 is critically damped.
+    pub contact_damping: f32,
+    /// Solves without bias after each substep, to remove the velocity added by pushing bodies apart.
+    pub relax_iterations: usize,
+    /// Maximum velocity used to push intersecting bodies apart.
+    pub max_push_velocity: f32,
+}
+
+impl Default for SubstepSettings {
+    fn default() -> Self {
+        SubstepSettings {
+            substeps: 4,
+            contact_frequency: 60.,
+            contact_damping: 1.,
+            relax_iterations: 1,
+            max_push_velocity: 3.,
+        }
+    }
+}
+
+impl SubstepSettings {
+    pub fn new(substeps: usize) -> SubstepSettings {
+        SubstepSettings {
+            substeps,
+            ..Default::default()
+        }
+    }
+
+    pub fn contact_softness(mut self, frequency: f32, damping: f32) -> Self {
+        self.contact_frequency = frequency;
+        self.contact_damping = damping;
+        self
+    }
+
+    pub fn relax_iterations(mut self, relax_iterations: usize) -> Self {
+        self.relax_iterations = relax_iterations;
+        self
+    }
+
+    pub fn max_push_velocity(mut self, max_push_velocity: f32) -> Self {
+        self.max_push_velocity = max_push_velocity;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Softness {
+    pub bias_rate: f32,
+    pub mass_scale: f32,
+    pub impulse_scale: f32,
+}
+
+impl Softness {
+    /// Softness of a damped spring with the given frequency (Hz) and damping ratio for the substep h.
+    pub fn new(frequency: f32, damping: f32, h: f32) -> Softness {
+        if frequency == 0. {
+            return Softness {
+                bias_rate: 0.,
+                mass_scale: 1.,
+                impulse_scale: 0.,
+            };
+        }
+
+        let omega = 2. * std::f32::consts::PI * frequency;
+        let a1 = 2. * damping + h * omega;
+        let a2 = h * omega * a1;
+        let a3 = 1. / (1. + a2);
+
+        Softness {
+            bias_rate: omega / a1,
+            mass_scale: a2 * a3,
+            impulse_scale: a3,
+        }
+    }
+}
+
+/// Location and rotation of a body at the start of the step.
+pub type Origin = (Vector2, f32);
+
+/// Contacts of a manifold at the end of a step, their impulses warm start the next step.
+#[derive(Clone, Copy, Debug)]
+pub struct CachedContacts {
+    normal: Vector2,
+    contacts: [Contact; 2],
+    contact_count: usize,
+}
+
+impl CachedContacts {
+    pub fn new(m: &Manifold) -> CachedContacts {
+        CachedContacts {
+            normal: m.normal,
+            contacts: m.contacts,
+            contact_count: m.contact_count,
+        }
+    }
+
+    /// Impulses of the cached contact closest to the location, if it is within the max distance.
+    fn impulses_at(&self, location: Vector2, max_distance: f32) -> Option<(f32, f32)> {
+        self.contacts
+            .iter()
+            .take(self.contact_count)
+            .map(|c| ((c.location - location).len_squared(), c))
+            .filter(|(distance, _)| *distance < max_distance * max_distance)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, c)| (c.substep_normal_impulse, c.substep_tangent_impulse))
+    }
+}
+
+/// Sets the contact up for the substeps, impulses of matching contacts in the previous step are reused.<br>
+/// Without them, heavy bodies resting on light ones sink in before the impulses have built up again.
+pub fn prepare_soft_contact(
+    m: &mut Manifold,
+    a: &Body,
+    b: &Body,
+    settings: &SolverSettings,
+    previous: Option<&CachedContacts>,
+) {
+    m.setup(a, b, settings.get_restitution_threshold());
+
+    let alignment = settings.get_warm_start_alignment();
+    let previous = previous.filter(|p| p.normal.dotted(m.normal) > alignment);
+
+    for c in m.contacts.iter_mut().take(m.contact_count) {
+        // separation is negative while intersecting
+        c.adjusted_separation = -m.depth - (c.diff_to_b - c.diff_to_a).dotted(m.normal);
+
+        let (normal_impulse, tangent_impulse) = previous
+            .and_then(|p| p.impulses_at(c.location, settings.get_warm_start_distance()))
+            .unwrap_or((0., 0.));
+
+        c.substep_normal_impulse = normal_impulse;
+        c.substep_tangent_impulse = tangent_impulse;
+    }
+
+    m.rolling_impulse = 0.;
+}
+
+pub fn warm_start_soft_contact(m: &mut Manifold, bodies: &mut [Body]) {
+    let (a, b) = collision_bodies(m.a_idx, m.b_idx, bodies);
+
+    for c in m.contacts.iter_mut().take(m.contact_count) {
+        let impulse = c.substep_normal_impulse * m.normal + c.substep_tangent_impulse * m.tangent;
+        apply_impulses(a, b, impulse, c.diff_to_a, c.diff_to_b);
+
+        c.normal_impulse += c.substep_normal_impulse;
+        c.tangent_impulse += c.substep_tangent_impulse;
+    }
+
+    a.transform.angular_velocity -= m.rolling_impulse * a.inverse_inertia;
+    b.transform.angular_velocity += m.rolling_impulse * b.inverse_inertia;
+}
+
+/// Solves the contact with bias if softness is given, otherwise only relaxes the velocities.
+pub fn solve_soft_contact(
+    m: &mut Manifold,
+    bodies: &mut [Body],
+    origins: &[Origin],
+    softness: Option<Softness>,
+    max_push_velocity: f32,
+    inv_h: f32,
+) {
+    let (origin_a, origin_b) = (origins[m.a_idx], origins[m.b_idx]);
+    let (a, b) = collision_bodies(m.a_idx, m.b_idx, bodies);
+    let mut total_jn = 0.;
+
+    for c in m.contacts.iter_mut().take(m.contact_count) {
+        // current separation, from how far the bodies moved since the contact was detected
+        let ra = Vector2::rotated(c.diff_to_a, a.transform.rotation - origin_a.1);
+        let rb = Vector2::rotated(c.diff_to_b, b.transform.rotation - origin_b.1);
+        let d = (b.transform.location - origin_b.0) - (a.transform.location - origin_a.0) + rb - ra;
+        let separation = d.dotted(m.normal) + c.adjusted_separation;
+
+        let (bias, mass_scale, impulse_scale) = match softness {
+            // speculative, do not push apart before the bodies actually touch
+            _ if separation > 0. => (separation * inv_h, 1., 0.),
+            Some(s) => (
+                f32::max(s.bias_rate * separation, -max_push_velocity),
+                s.mass_scale,
+                s.impulse_scale,
+            ),
+            None => (0., 1., 0.),
+        };
+
+        // normal impulse
+        let v_rel_n = relative_velocity(a, b, c.diff_to_a, c.diff_to_b).dotted(m.normal);
+        let jn = -c.normal_magnitude * mass_scale * (v_rel_n + bias)
+            - impulse_scale * c.substep_normal_impulse;
+
+        let previous = c.substep_normal_impulse;
+        c.substep_normal_impulse = f32::max(previous + jn, 0.);
+        let jn = c.substep_normal_impulse - previous;
+
+        apply_impulses(a, b, jn * m.normal, c.diff_to_a, c.diff_to_b);
+        c.normal_impulse += jn;
+        total_jn += c.substep_normal_impulse;
+
+        // friction impulse
+        let v_rel_t = relative_velocity(a, b, c.diff_to_a, c.diff_to_b).dotted(m.tangent)
+            - m.surface_velocity;
+        let jt = -c.tangent_magnitude * v_rel_t;
+
+        let max_friction = m.friction * c.substep_normal_impulse;
+        let previous = c.substep_tangent_impulse;
+        c.substep_tangent_impulse = (previous + jt).clamp(-max_friction, max_friction);
+        let jt = c.substep_tangent_impulse - previous;
+
+        apply_impulses(a, b, jt * m.tangent, c.diff_to_a, c.diff_to_b);
+        c.tangent_impulse += jt;
+    }
+
+    // rolling resistance
+    let inverse_inertia = a.inverse_inertia + b.inverse_inertia;
+
+    if m.rolling_resistance > 0. && inverse_inertia > 0. {
+        let w_rel = b.transform.angular_velocity - a.transform.angular_velocity;
+        let max_rolling = total_jn * m.rolling_resistance;
+
+        let previous = m.rolling_impulse;
+        m.rolling_impulse = (previous - w_rel / inverse_inertia).clamp(-max_rolling, max_rolling);
+        let jr = m.rolling_impulse - previous;
+
+        a.transform.angular_velocity -= jr * a.inverse_inertia;
+        b.transform.angular_velocity += jr * b.inverse_inertia;
+    }
+}
+
+/// Bounces contacts that approached fast enough, relative to their velocity before solving.
+pub fn apply_restitution(m: &mut Manifold, bodies: &mut [Body]) {
+    let (a, b) = collision_bodies(m.a_idx, m.b_idx, bodies);
+
+    for c in m.contacts.iter_mut().take(m.contact_count) {
+        if c.bounce_velocity == 0. || c.substep_normal_impulse == 0. {
+            continue;
+        }
+
+        let v_rel_n = relative_velocity(a, b, c.diff_to_a, c.diff_to_b).dotted(m.normal);
+        let jn = c.normal_magnitude * (c.bounce_velocity - v_rel_n);
+
+        let previous = c.substep_normal_impulse;
+        c.substep_normal_impulse = f32::max(previous + jn, 0.);
+        let jn = c.substep_normal_impulse - previous;
+
+        apply_impulses(a, b, jn * m.normal, c.diff_to_a, c.diff_to_b);
+        c.normal_impulse += jn;
+    }
+}
+
+// --------------------------------- UTILITY FUNCTIONS ---------------------------------
+fn relative_velocity(a: &Body, b: &Body, ac: Vector2, bc: Vector2) -> Vector2 {
+    let mut v_rel = b.transform.velocity + cross(b.transform.angular_velocity, bc);
+    v_rel -= a.transform.velocity + cross(a.transform.angular_velocity, ac);
+    v_rel
+}
+
+fn apply_impulses(a: &mut Body, b: &mut Body, impulse: Vector2, ac: Vector2, bc: Vector2) {
+    a.transform.velocity -= impulse * a.inverse_mass;
+    a.transform.angular_velocity -= ac.crossed(impulse) * a.inverse_inertia;
+
+    b.transform.velocity += impulse * b.inverse_mass;
+    b.transform.angular_velocity += bc.crossed(impulse) * b.inverse_inertia;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::material::{Material, DEFAULT},
+        joints::{Joint, RevoluteJoint},
+        math::vector2::ZERO,
+        test_utils::{run, world},
+    };
+
+    /// Heavy box resting on a light one, returns their heights after five seconds.
+    fn heavy_on_light(substeps: usize, light_size: f32, heavy_size: f32, ratio: f32) -> (f32, f32) {
+        let mut world = world();
+        world.set_sleeping_enabled(false);
+        world
+            .get_solver_settings_mut()
+            .set_substepping(Some(SubstepSettings::new(substeps)));
+
+        let heavy = Material::new(DEFAULT.density * ratio, 0.6, 0., "heavy");
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let light = world.add_body(Body::aabb(
+            0.,
+            light_size * 0.5,
+            light_size,
+            light_size,
+            DEFAULT,
+        ));
+        let top = world.add_body(Body::aabb(
+            0.,
+            light_size + heavy_size * 0.5,
+            heavy_size,
+            heavy_size,
+            heavy,
+        ));
+
+        run(&mut world, 300);
+
+        let bodies = world.get_bodies();
+        (
+            bodies[light].transform.location.y,
+            bodies[top].transform.location.y,
+        )
+    }
+
+    #[test]
+    fn substeps_hold_heavy_bodies_on_light_ones() {
+        for (substeps, light_size, heavy_size, ratio) in
+            [(4, 0.5, 2., 30.), (8, 1., 1., 100.), (16, 1., 1., 100.)]
+        {
+            let (light, top) = heavy_on_light(substeps, light_size, heavy_size, ratio);
+
+            assert!(
+                (light - light_size * 0.5).abs() < 0.03,
+                "light box sank to {light} with {substeps} substeps"
+            );
+            assert!(
+                (top - light_size - heavy_size * 0.5).abs() < 0.05,
+                "heavy box sank to {top} with {substeps} substeps"
+            );
+        }
+    }
+
+    #[test]
+    fn substeps_keep_contacts_after_removing_a_body() {
+        let mut world = world();
+        world.set_sleeping_enabled(false);
+        world
+            .get_solver_settings_mut()
+            .set_substepping(Some(SubstepSettings::default()));
+
+        let falling = world.add_body(Body::circle(-5., 5., 0.5, DEFAULT));
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        world.add_body(Body::aabb(0., 0.5, 1., 1., DEFAULT));
+        world.add_body(Body::aabb(0., 1.5, 1., 1., DEFAULT));
+
+        run(&mut world, 60);
+        world.remove_body(falling);
+        run(&mut world, 120);
+
+        let top = &world.get_bodies()[2];
+        assert!(
+            (top.transform.location.y - 1.5).abs() < 0.03,
+            "{}",
+            top.transform.location
+        );
+    }
+
+    #[test]
+    fn substeps_keep_jointed_bodies_together() {
+        let mut world = world();
+        world
+            .get_solver_settings_mut()
+            .set_substepping(Some(SubstepSettings::default()));
+
+        let anchor = world.add_body(Body::platform_circle(0., 0., 0.1, DEFAULT));
+        let bob = world.add_body(Body::circle(3., 0., 0.5, DEFAULT));
+
+        let bodies = world.get_bodies();
+        let joint = RevoluteJoint::new(&bodies[anchor], anchor, &bodies[bob], bob, ZERO);
+        world.add_joint(Joint::Revolute(joint)).unwrap();
+
+        for _ in 0..300 {
+            world.update();
+
+            let distance = world.get_bodies()[bob].transform.location.len();
+            assert!((distance - 3.).abs() < 0.05, "bob is {distance} away");
+        }
+    }
+}
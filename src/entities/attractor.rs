@@ -6,18 +6,13 @@ use crate::{
     math::{vector2::ZERO, Vector2},
 };
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Default)]
 pub enum AttractorType {
+    #[default]
     Global,
     Local,
 }
 
-impl Default for AttractorType {
-    fn default() -> Self {
-        Self::Global
-    }
-}
-
 #[derive(Default)]
 /// This attractor produces a pulling force towards it, that gets stronger the bodies are to it.<br>
 /// It also has a maximum range that can be defined (as in bodies beyond the range do not get affected at all).
@@ -11,10 +11,13 @@
 use std::fmt::Display;
 
 use crate::{
-    collision::Hitbox,
+    collision::{CollisionFilter, Hitbox},
     entities::material::Material,
     entities::transform::Transform,
-    math::{vector2::ZERO, Vector2},
+    math::{
+        vector2::{cross, ZERO},
+        Vector2,
+    },
     shapes::Shape,
     shapes::*,
 };
@@ -36,8 +39,6 @@ impl Display for BodyType {
 
 use BodyType::*;
 
-const RESOLUTION_VELOCITY_CONSTRAINT: f32 = 0.0008831703;
-
 /// DO NOT CHOOSE VALUE BELOW 4
 pub const MAX_VERTICE_COUNT: usize = 8;
 
@@ -58,6 +59,42 @@ pub struct Body {
     pub inverse_mass: f32,
     pub inertia: f32,
     pub inverse_inertia: f32,
+    /// Change via World::set_filter, so outdated contacts get discarded.
+    pub filter: CollisionFilter,
+    /// Sensors detect overlaps with other bodies, without ever colliding with them.
+    pub is_sensor: bool,
+    /// Other bodies only collide with this one if they are pushed out along this direction,
+    /// e.g. (0, 1) for platforms that can be jumped through from below.
+    pub one_way: Option<Vector2>,
+    /// Speed the surface moves with, positive values move it clockwise (to the right on top).<br>
+    /// Bodies touching it get dragged along by friction, like on a conveyor belt.
+    pub surface_velocity: f32,
+    /// Share of the linear velocity lost per second, e.g. to air resistance.
+    pub linear_damping: f32,
+    /// Share of the angular velocity lost per second.
+    pub angular_damping: f32,
+    /// Scales the world forces and attractors, 0 lets the body float.
+    pub gravity_scale: f32,
+    pub max_linear_speed: Option<f32>,
+    pub max_angular_speed: Option<f32>,
+    /// Change via set_fixed_rotation, so the mass data gets updated.
+    pub(crate) fixed_rotation: bool,
+
+    /// Transform at the start of the last step, for interpolation between steps.
+    pub(crate) previous_transform: Transform,
+
+    /// Forces and torque applied until the end of the next step.
+    pub(crate) force: Vector2,
+    pub(crate) torque: f32,
+
+    /// Velocities of the position correction, only applied for a single step.
+    pub(crate) pseudo_velocity: Vector2,
+    pub(crate) pseudo_angular_velocity: f32,
+
+    pub(crate) sleeping: bool,
+    pub(crate) sleep_time: f32,
+    pub(crate) sleep_velocity: (Vector2, f32),
+    pub(crate) island: usize,
 }
 
 // --------------------------------- GENERIC CONSTRUCTOR ---------------------------------
@@ -103,6 +140,23 @@ impl Body {
     }
 }
 
+impl Body {
+    /// Static body used to query the world with a shape, it never gets added to the world.
+    pub(crate) fn probe(shape: Shape, location: Vector2, rotation: f32) -> Body {
+        let mut probe = Body::new(
+            location.x,
+            location.y,
+            shape,
+            Static,
+            Material::default(),
+            None,
+        );
+
+        probe.rotate_fixed_angle(rotation);
+        probe
+    }
+}
+
 fn calc_mass(area: f32, density: f32) -> (f32, f32) {
     let mass = area * density;
 
@@ -139,16 +193,68 @@ impl Body {
     pub fn rotate(&mut self, dt: f32) {
         let angle = self.transform.angular_velocity;
 
-        if !matches!(self.shape, Shape::AABB(_)) && angle != 0.0 {
+        if !matches!(self.shape, Shape::AABB(_)) && !self.fixed_rotation && angle != 0.0 {
             let (vertices, len) = self.get_vertices();
             for (idx, v) in vertices.into_iter().enumerate().take(len) {
                 self.vertices[idx] = Vector2::rotated(v, angle * dt);
             }
+            self.transform.rotation += angle * dt;
         }
 
         self.update_hitbox();
     }
 
+    /// Moves the body by its velocities and the pseudo velocities, which are discarded afterwards.<br>
+    /// The velocities get limited to the max speeds first.
+    pub(crate) fn integrate_velocity(&mut self, dt: f32) {
+        self.limit_speed();
+
+        let angle = (self.transform.angular_velocity + self.pseudo_angular_velocity) * dt;
+
+        self.transform.location += (self.transform.velocity + self.pseudo_velocity) * dt;
+
+        if self.fixed_rotation {
+            self.update_hitbox();
+        } else {
+            self.rotate_fixed_angle(angle);
+        }
+
+        self.pseudo_velocity = ZERO;
+        self.pseudo_angular_velocity = 0.0;
+    }
+
+    /// Accelerates the body by the accumulated force and torque, which are kept until cleared.
+    pub(crate) fn integrate_force(&mut self, dt: f32) {
+        self.transform.velocity += self.force * self.inverse_mass * dt;
+        self.transform.angular_velocity += self.torque * self.inverse_inertia * dt;
+    }
+
+    /// Damping is applied implicitly, so it stays stable for any time step.
+    pub(crate) fn damp_velocity(&mut self, dt: f32) {
+        self.transform.velocity *= 1.0 / (1.0 + dt * self.linear_damping);
+        self.transform.angular_velocity *= 1.0 / (1.0 + dt * self.angular_damping);
+    }
+
+    fn limit_speed(&mut self) {
+        if let Some(max_speed) = self.max_linear_speed {
+            let speed = self.transform.velocity.len();
+
+            if speed > max_speed {
+                self.transform.velocity *= max_speed / speed;
+            }
+        }
+
+        if let Some(max_speed) = self.max_angular_speed {
+            let w = &mut self.transform.angular_velocity;
+            *w = w.clamp(-max_speed, max_speed);
+        }
+    }
+
+    pub(crate) fn clear_forces(&mut self) {
+        self.force = ZERO;
+        self.torque = 0.0;
+    }
+
     fn update_hitbox(&mut self) {
         if matches!(self.shape, Shape::Circle(_)) || matches!(self.shape, Shape::AABB(_)) {
             return;
@@ -223,17 +329,101 @@ impl Body {
 
 // --------------------------------- METHODS ---------------------------------
 impl Body {
-    pub fn apply_impulse(&mut self, impulse: Vector2) {
-        if impulse.abs().len_squared() > RESOLUTION_VELOCITY_CONSTRAINT {
+    /// Ignores impulses whose squared length does not exceed the threshold,
+    /// usually the impulse threshold of the solver settings (see World::apply_impulse).
+    pub fn apply_impulse(&mut self, impulse: Vector2, threshold: f32) {
+        if impulse.abs().len_squared() > threshold {
+            self.wake();
             self.transform.velocity += impulse;
         }
     }
 
+    /// Applies the force during the next step, forces accumulate until then.
+    pub fn apply_force(&mut self, force: Vector2) {
+        if self.body_type == Static {
+            return;
+        }
+
+        self.wake();
+        self.force += force;
+    }
+
+    /// Applies the force during the next step, off-center points also add torque.
+    pub fn apply_force_at_point(&mut self, force: Vector2, point: Vector2) {
+        if self.body_type == Static {
+            return;
+        }
+
+        self.wake();
+        self.force += force;
+        self.torque += (point - self.transform.location).crossed(force);
+    }
+
+    /// Applies the torque during the next step, positive values rotate counter-clockwise.
+    pub fn apply_torque(&mut self, torque: f32) {
+        if self.body_type == Static {
+            return;
+        }
+
+        self.wake();
+        self.torque += torque;
+    }
+
+    /// Changes the angular velocity immediately, scaled by the inertia.
+    pub fn apply_angular_impulse(&mut self, impulse: f32) {
+        if self.body_type == Static {
+            return;
+        }
+
+        self.wake();
+        self.transform.angular_velocity += impulse * self.inverse_inertia;
+    }
+
+    /// Changes the linear and angular velocity, according to where the impulse hits the body.<br>
+    /// Unlike apply_impulse, the impulse gets scaled by the mass and small ones are not ignored.
+    pub fn apply_impulse_at_point(&mut self, impulse: Vector2, point: Vector2) {
+        if self.body_type == Static {
+            return;
+        }
+
+        self.wake();
+        self.transform.velocity += impulse * self.inverse_mass;
+        self.transform.angular_velocity +=
+            (point - self.transform.location).crossed(impulse) * self.inverse_inertia;
+    }
+
     pub fn set_mass(&mut self, mass: f32) {
         self.mass = mass;
         self.inverse_mass = 1.0 / mass;
+        self.update_inertia();
+    }
+
+    /// Bodies with fixed rotation keep their orientation, collisions and joints cannot turn them.
+    pub fn set_fixed_rotation(&mut self, fixed_rotation: bool) {
+        self.fixed_rotation = fixed_rotation;
+
+        if self.body_type == Dynamic {
+            self.update_inertia();
+        }
+    }
+
+    pub fn is_rotation_fixed(&self) -> bool {
+        self.fixed_rotation
+    }
+
+    /// The inertia stays known while the rotation is fixed, only the solver ignores it.
+    fn update_inertia(&mut self) {
         (self.inertia, self.inverse_inertia) =
-            calc_inertia(&self.shape, mass, self.material.density);
+            calc_inertia(&self.shape, self.mass, self.material.density);
+
+        if self.fixed_rotation {
+            self.inverse_inertia = 0.0;
+            self.transform.angular_velocity = 0.0;
+        }
+    }
+
+    pub fn can_collide_with(&self, other: &Body) -> bool {
+        self.filter.should_collide(&other.filter)
     }
 
     pub fn encloses(&self, p: Vector2) -> bool {
@@ -280,11 +470,62 @@ impl Body {
             for (idx, v) in vertices.iter().enumerate().take(len) {
                 self.vertices[idx] = Vector2::rotated(*v, angle);
             }
+            self.transform.rotation += angle;
         }
         self.update_hitbox();
     }
 }
 
+// --------------------------------- KINETIC STATE ---------------------------------
+impl Body {
+    /// Bodies rotate around their location, which is their center of mass.
+    pub fn center_of_mass(&self) -> Vector2 {
+        self.transform.location
+    }
+
+    /// Velocity of the body at the given point in world coordinates, including its rotation.
+    pub fn velocity_at_point(&self, point: Vector2) -> Vector2 {
+        let r = point - self.transform.location;
+        self.transform.velocity + cross(self.transform.angular_velocity, r)
+    }
+
+    pub fn linear_momentum(&self) -> Vector2 {
+        self.transform.velocity * self.mass
+    }
+
+    /// Angular momentum around the center of mass.<br>
+    /// Use World::total_angular_momentum for the sum around the origin.
+    pub fn angular_momentum(&self) -> f32 {
+        self.transform.angular_velocity * self.inertia
+    }
+
+    pub fn kinetic_energy(&self) -> f32 {
+        let v = self.transform.velocity;
+        let w = self.transform.angular_velocity;
+
+        0.5 * (self.mass * v.len_squared() + self.inertia * w * w)
+    }
+}
+
+// --------------------------------- INTERPOLATION ---------------------------------
+impl Body {
+    pub fn get_previous_transform(&self) -> &Transform {
+        &self.previous_transform
+    }
+
+    /// Blends the previous and the current transform, alpha 0 being the previous one.<br>
+    /// Use it with World::get_interpolation_alpha to render smoothly between steps.
+    pub fn interpolated_transform(&self, alpha: f32) -> Transform {
+        let (previous, current) = (&self.previous_transform, &self.transform);
+
+        Transform {
+            location: previous.location + (current.location - previous.location) * alpha,
+            rotation: previous.rotation + (current.rotation - previous.rotation) * alpha,
+            ..current.clone()
+        }
+    }
+}
+
 // --------------------------------- EXPERIMENTAL ---------------------------------
 impl Body {
     pub fn halt(&mut self) {
@@ -296,17 +537,55 @@ impl Body {
         self.transform.velocity.y = 0.0;
         self.transform.angular_velocity = 0.0;
     }
+}
 
-    pub fn clamp_small_movement(&mut self) {
-        let abs_v = self.transform.velocity.abs();
+// --------------------------------- SLEEPING ---------------------------------
+/// Weight of the current velocity in the smoothed velocity that gets compared to the tolerances.<br>
+/// Resting bodies jitter back and forth, which cancels out when smoothed, while actual movement does not.
+const SLEEP_VELOCITY_SMOOTHING: f32 = 0.2;
 
-        if abs_v.x <= 0.003 && abs_v.y <= 0.003 {
-            self.transform.velocity = ZERO;
-        }
+impl Body {
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
 
-        if self.transform.angular_velocity.abs() < 0.0004 {
-            self.transform.angular_velocity = 0.;
+    /// Wakes only this body, use World::wake to wake the whole island it belongs to.
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.sleep_time = 0.0;
+        self.sleep_velocity = (ZERO, 0.0);
+    }
+
+    /// Active bodies get integrated and solved, which are all dynamic bodies that are awake.
+    pub fn is_active(&self) -> bool {
+        self.body_type == Dynamic && !self.sleeping
+    }
+
+    /// Advances the time this body has been resting and returns it.<br>
+    /// The body rests while its smoothed velocities stay below the given tolerances.
+    pub(crate) fn update_sleep_time(&mut self, dt: f32, linear: f32, angular: f32) -> f32 {
+        let (v, w) = &mut self.sleep_velocity;
+        let s = SLEEP_VELOCITY_SMOOTHING;
+
+        *v = *v * (1. - s) + self.transform.velocity * s;
+        *w = *w * (1. - s) + self.transform.angular_velocity * s;
+
+        let v = v.len_squared();
+        let w = w.abs();
+
+        if v > linear * linear || w > angular {
+            self.sleep_time = 0.0;
+        } else {
+            self.sleep_time += dt;
         }
+
+        self.sleep_time
+    }
+
+    pub(crate) fn sleep(&mut self) {
+        self.sleeping = true;
+        self.transform.velocity = ZERO;
+        self.transform.angular_velocity = 0.0;
     }
 }
 
@@ -385,6 +664,7 @@ impl Body {
         for v in &mut pr.vertices {
             *v = Vector2::rotated(*v, rotation);
         }
+        pr.transform.rotation = rotation;
 
         pr.generate_hitbox();
 
@@ -425,6 +705,7 @@ impl Body {
             for v in &mut pp.vertices {
                 *v = Vector2::rotated(*v, rotation);
             }
+            pp.transform.rotation = rotation;
 
             pp.generate_hitbox();
 
@@ -471,6 +752,275 @@ impl Default for Body {
             inverse_mass: 0.0,
             inertia: 0.0,
             inverse_inertia: 0.0,
+            filter: CollisionFilter::default(),
+            is_sensor: false,
+            one_way: None,
+            surface_velocity: 0.0,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            gravity_scale: 1.0,
+            max_linear_speed: None,
+            max_angular_speed: None,
+            fixed_rotation: false,
+            previous_transform: Default::default(),
+            force: ZERO,
+            torque: 0.0,
+            pseudo_velocity: ZERO,
+            pseudo_angular_velocity: 0.0,
+            sleeping: false,
+            sleep_time: 0.0,
+            sleep_velocity: (ZERO, 0.0),
+            island: 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{
+            attractor::{Attractor, AttractorType},
+            material::{Material, DEFAULT},
+        },
+        environment::world::World,
+        test_utils::{run, world},
+    };
+
+    #[test]
+    fn impulses_below_the_threshold_are_ignored() {
+        let mut light = Body::aabb(0., 0., 1., 1., DEFAULT);
+        let mut heavy = Body::aabb(0., 0., 4., 4., DEFAULT);
+
+        light.apply_impulse(Vector2::new(2., 0.), 0.01);
+        heavy.apply_impulse(Vector2::new(2., 0.), 0.01);
+        assert_eq!(light.transform.velocity, Vector2::new(2., 0.));
+        assert_eq!(heavy.transform.velocity, Vector2::new(2., 0.));
+
+        light.apply_impulse(Vector2::new(0., 0.05), 0.01);
+        assert_eq!(light.transform.velocity.y, 0.);
+    }
+
+    #[test]
+    fn impulses_at_points_scale_with_mass_and_spin() {
+        let mut light = Body::aabb(0., 0., 1., 1., DEFAULT);
+        let mut heavy = Body::aabb(0., 0., 2., 2., DEFAULT);
+        let impulse = Vector2::new(0., 1.);
+
+        light.apply_impulse_at_point(impulse, ZERO);
+        heavy.apply_impulse_at_point(impulse, ZERO);
+        let ratio = light.transform.velocity.y / heavy.transform.velocity.y;
+        assert!((ratio - heavy.mass / light.mass).abs() < 0.001);
+        assert_eq!(light.transform.angular_velocity, 0.);
+
+        // pushing the right edge up turns the body counter-clockwise
+        let mut rotating = Body::obb(0., 0., 1., 1., DEFAULT);
+        rotating.apply_impulse_at_point(impulse, Vector2::new(0.5, 0.));
+        assert!(rotating.transform.angular_velocity > 0.);
+
+        let mut spinning = Body::obb(0., 0., 1., 1., DEFAULT);
+        spinning.apply_angular_impulse(1.);
+        assert!((spinning.transform.angular_velocity - spinning.inverse_inertia).abs() < 0.001);
+    }
+
+    #[test]
+    fn static_bodies_ignore_forces_and_impulses() {
+        let mut wall = Body::platform_rectangle_aabb(0., 0., 1., 1., DEFAULT);
+
+        wall.apply_force(Vector2::new(1., 0.));
+        wall.apply_force_at_point(Vector2::new(1., 0.), Vector2::new(0., 0.5));
+        wall.apply_torque(1.);
+        wall.apply_angular_impulse(1.);
+        wall.apply_impulse_at_point(Vector2::new(1., 0.), Vector2::new(0., 0.5));
+
+        assert_eq!(wall.force, ZERO);
+        assert_eq!(wall.torque, 0.);
+        assert_eq!(wall.transform.velocity, ZERO);
+        assert_eq!(wall.transform.angular_velocity, 0.);
+    }
+
+    #[test]
+    fn forces_act_for_a_single_step() {
+        let mut world = World::new(60., 10.);
+        let body = world.add_body(Body::obb(0., 0., 1., 1., DEFAULT));
+        let mass = world.get_bodies()[body].mass;
+
+        let body_mut = world.get_body_mut(body).unwrap();
+        body_mut.apply_force(Vector2::new(mass * 30., 0.));
+        body_mut.apply_force(Vector2::new(mass * 30., 0.));
+        body_mut.apply_torque(1.);
+        world.update();
+
+        let velocity = world.get_bodies()[body].transform.velocity;
+        let spin = world.get_bodies()[body].transform.angular_velocity;
+        assert!((velocity.x - 1.).abs() < 0.001, "{velocity}");
+        assert!(spin > 0.);
+
+        // the accumulators were cleared after the step
+        world.update();
+        assert_eq!(world.get_bodies()[body].transform.velocity, velocity);
+        assert_eq!(world.get_bodies()[body].transform.angular_velocity, spin);
+    }
+
+    /// Vertical velocity of a body after one step of gravity and an attractor below it.
+    fn falling_velocity(gravity_scale: f32) -> f32 {
+        let mut world = world();
+        world.add_attractor(Attractor::new(0., -20., 1., AttractorType::Global, None).mass(1e6));
+
+        let mut body = Body::circle(0., 0., 0.5, DEFAULT);
+        body.gravity_scale = gravity_scale;
+        let body = world.add_body(body);
+
+        world.update();
+        world.get_bodies()[body].transform.velocity.y
+    }
+
+    #[test]
+    fn gravity_scale_applies_to_forces_and_attractors() {
+        let (floating, half, full) = (
+            falling_velocity(0.),
+            falling_velocity(0.5),
+            falling_velocity(1.),
+        );
+
+        assert_eq!(floating, 0.);
+        assert!(full < -9.81 * 0.5, "fell with {full}");
+        assert!(
+            (half - full * 0.5).abs() < 0.01,
+            "{half} instead of {full} / 2"
+        );
+    }
+
+    #[test]
+    fn damping_slows_bodies_down_over_time() {
+        let mut world = World::new(60., 10.);
+        world.set_sleeping_enabled(false);
+
+        let mut body = Body::obb(0., 0., 1., 1., DEFAULT);
+        body.transform.velocity = Vector2::new(10., 0.);
+        body.transform.angular_velocity = 10.;
+        body.linear_damping = 1.;
+        body.angular_damping = 2.;
+        let body = world.add_body(body);
+
+        run(&mut world, 60);
+
+        // roughly e^-1 and e^-2 after a second
+        let transform = &world.get_bodies()[body].transform;
+        assert!(
+            (transform.velocity.x - 3.7).abs() < 0.1,
+            "{}",
+            transform.velocity
+        );
+        assert!((transform.angular_velocity - 1.36).abs() < 0.1);
+    }
+
+    #[test]
+    fn max_speeds_cap_velocities() {
+        let mut world = world();
+        let mut body = Body::obb(0., 0., 1., 1., DEFAULT);
+        body.max_linear_speed = Some(2.);
+        body.max_angular_speed = Some(1.);
+        body.transform.angular_velocity = -5.;
+        let body = world.add_body(body);
+
+        run(&mut world, 60);
+
+        let transform = &world.get_bodies()[body].transform;
+        assert!((transform.velocity.len() - 2.).abs() < 0.001);
+        assert_eq!(transform.angular_velocity, -1.);
+    }
+
+    #[test]
+    fn fixed_rotation_can_be_toggled() {
+        let mut body = Body::obb(0., 0., 1., 2., DEFAULT);
+        let inverse_inertia = body.inverse_inertia;
+        body.transform.angular_velocity = 1.;
+
+        body.set_fixed_rotation(true);
+        assert_eq!(body.inverse_inertia, 0.);
+        assert_eq!(body.transform.angular_velocity, 0.);
+        assert!(body.inertia > 0.);
+
+        body.transform.angular_velocity = 1.;
+        body.rotate(1.);
+        assert_eq!(body.transform.rotation, 0.);
+
+        body.set_fixed_rotation(false);
+        assert_eq!(body.inverse_inertia, inverse_inertia);
+    }
+
+    /// Rotation of a tall box on ground after getting pushed at its top for a second.
+    fn pushed_rotation(fixed_rotation: bool) -> f32 {
+        let mut world = world();
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let mut player = Body::obb(0., 1., 0.5, 2., DEFAULT);
+        player.set_fixed_rotation(fixed_rotation);
+        let player = world.add_body(player);
+
+        for _ in 0..60 {
+            let body = world.get_body_mut(player).unwrap();
+            let top = body.transform.location + Vector2::new(0., 1.);
+            let push = Vector2::new(body.mass * 5., 0.);
+            body.apply_force_at_point(push, top);
+            world.update();
+        }
+
+        world.get_bodies()[player].transform.rotation
+    }
+
+    #[test]
+    fn fixed_rotation_keeps_bodies_upright() {
+        assert!(pushed_rotation(false).abs() > 0.5);
+        assert_eq!(pushed_rotation(true), 0.);
+    }
+
+    #[test]
+    fn kinetic_state_follows_the_velocities() {
+        let mut body = Body::obb(0., 0., 2., 2., DEFAULT);
+        body.transform.velocity = Vector2::new(1., 0.);
+        body.transform.angular_velocity = 2.;
+
+        // counter-clockwise spin moves the right side up and the top to the left
+        assert_eq!(
+            body.velocity_at_point(Vector2::new(1., 0.)),
+            Vector2::new(1., 2.)
+        );
+        assert_eq!(
+            body.velocity_at_point(Vector2::new(0., 1.)),
+            Vector2::new(-1., 0.)
+        );
+
+        assert_eq!(body.linear_momentum(), Vector2::new(body.mass, 0.));
+        assert_eq!(body.angular_momentum(), 2. * body.inertia);
+
+        let energy = 0.5 * body.mass + 2. * body.inertia;
+        assert!((body.kinetic_energy() - energy).abs() < 0.001);
+    }
+
+    #[test]
+    fn collisions_conserve_total_momentum() {
+        let mut world = World::new(60., 10.);
+        world.add_body(Body::platform_rectangle_aabb(0., -10., 20., 1., DEFAULT));
+        let slippery = Material::new(1., 0., 0.5, "slippery");
+
+        let mut a = Body::circle(-3., 0.2, 0.5, slippery.clone());
+        a.transform.velocity = Vector2::new(4., 0.);
+        let mut b = Body::obb(3., 0., 1., 1., slippery);
+        b.transform.velocity = Vector2::new(-1., 0.);
+        world.add_body(a);
+        let b = world.add_body(b);
+
+        let momentum = world.total_linear_momentum();
+        let angular_momentum = world.total_angular_momentum();
+        let energy = world.total_kinetic_energy();
+
+        run(&mut world, 120);
+
+        assert!(world.get_bodies()[b].transform.velocity.x > 0.);
+        assert!((world.total_linear_momentum() - momentum).len() < 0.01);
+        assert!((world.total_angular_momentum() - angular_momentum).abs() < 0.05);
+        // the collision is not fully elastic
+        assert!(world.total_kinetic_energy() < energy);
+    }
+}
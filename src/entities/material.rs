@@ -8,18 +8,110 @@ pub struct Material {
     pub density: f32,
     pub friction: f32,
     pub restitution: f32,
+    /// Slows down rolling circles, relative to the normal force and the radius.<br>
+    /// Zero for all presets, so circles keep rolling unless a material opts in.
+    pub rolling_resistance: f32,
+    pub friction_combine: CombineMode,
+    pub restitution_combine: CombineMode,
+    pub rolling_combine: CombineMode,
     pub name: &'static str,
 }
 
+/// Defines how the values of two touching materials are combined.<br>
+/// If the materials use different modes, the one declared later wins:
+/// Average < GeometricMean < Min < Multiply < Max
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CombineMode {
+    #[default]
+    Average,
+    GeometricMean,
+    Min,
+    Multiply,
+    Max,
+}
+
+impl CombineMode {
+    pub fn combine(self, a: f32, b: f32) -> f32 {
+        match self {
+            CombineMode::Average => (a + b) * 0.5,
+            CombineMode::GeometricMean => (a * b).sqrt(),
+            CombineMode::Min => f32::min(a, b),
+            CombineMode::Multiply => a * b,
+            CombineMode::Max => f32::max(a, b),
+        }
+    }
+}
+
 impl Material {
     pub const fn new(density: f32, friction: f32, restitution: f32, t: &'static str) -> Self {
         Self {
             density,
             friction,
             restitution,
+            rolling_resistance: 0.,
+            friction_combine: CombineMode::Average,
+            restitution_combine: CombineMode::Average,
+            rolling_combine: CombineMode::Average,
             name: t,
         }
     }
+
+    pub const fn rolling_resistance(mut self, rolling_resistance: f32) -> Self {
+        self.rolling_resistance = rolling_resistance;
+        self
+    }
+
+    pub const fn friction_combine(mut self, mode: CombineMode) -> Self {
+        self.friction_combine = mode;
+        self
+    }
+
+    pub const fn restitution_combine(mut self, mode: CombineMode) -> Self {
+        self.restitution_combine = mode;
+        self
+    }
+
+    pub const fn rolling_combine(mut self, mode: CombineMode) -> Self {
+        self.rolling_combine = mode;
+        self
+    }
+
+    /// Combined friction, restitution and rolling resistance of two touching materials.
+    pub fn combine(&self, other: &Material) -> (f32, f32, f32) {
+        let friction_mode = Ord::max(self.friction_combine, other.friction_combine);
+        let restitution_mode = Ord::max(self.restitution_combine, other.restitution_combine);
+        let rolling_mode = Ord::max(self.rolling_combine, other.rolling_combine);
+
+        (
+            friction_mode.combine(self.friction, other.friction),
+            restitution_mode.combine(self.restitution, other.restitution),
+            rolling_mode.combine(self.rolling_resistance, other.rolling_resistance),
+        )
+    }
+}
+
+/// Friction and restitution that replace the combined values of two specific materials.<br>
+/// The rolling resistance is only replaced if given.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MaterialPair {
+    pub friction: f32,
+    pub restitution: f32,
+    pub rolling_resistance: Option<f32>,
+}
+
+impl MaterialPair {
+    pub fn new(friction: f32, restitution: f32) -> MaterialPair {
+        MaterialPair {
+            friction,
+            restitution,
+            rolling_resistance: None,
+        }
+    }
+
+    pub fn rolling_resistance(mut self, rolling_resistance: f32) -> Self {
+        self.rolling_resistance = Some(rolling_resistance);
+        self
+    }
 }
 
 impl Default for Material {
@@ -33,3 +125,70 @@ pub const RUBBER: Material = Material::new(0.9, 0.8, 0.6, "rubber");
 pub const PLASTIC: Material = Material::new(1.2, 0.6, 0.5, "plastic");
 pub const STONE: Material = Material::new(2.5, 0.4, 0.2, "stone");
 pub const METAL: Material = Material::new(7.8, 0.2, 0.1, "metal");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bounce_height, world};
+
+    #[test]
+    fn presets_do_not_resist_rolling() {
+        for material in [DEFAULT, RUBBER, PLASTIC, STONE, METAL] {
+            assert_eq!(material.rolling_resistance, 0., "{}", material.name);
+        }
+    }
+
+    #[test]
+    fn combine_modes_pick_the_later_declared_mode() {
+        let ice = Material::new(1., 0.1, 0.2, "ice").friction_combine(CombineMode::Min);
+        let rubber = Material::new(1., 0.9, 0.8, "rubber")
+            .friction_combine(CombineMode::Max)
+            .restitution_combine(CombineMode::Multiply);
+
+        let (friction, restitution, _) = ice.combine(&RUBBER);
+        assert_eq!(friction, 0.1);
+        assert_eq!(restitution, (0.2 + RUBBER.restitution) * 0.5);
+
+        let (friction, restitution, _) = ice.combine(&rubber);
+        assert_eq!(friction, 0.9);
+        assert!((restitution - 0.16).abs() < 1e-6);
+    }
+
+    #[test]
+    fn combine_modes_combine_both_values() {
+        assert_eq!(CombineMode::Average.combine(0.2, 0.8), 0.5);
+        assert_eq!(CombineMode::GeometricMean.combine(0.25, 1.), 0.5);
+        assert_eq!(CombineMode::Min.combine(0.2, 0.8), 0.2);
+        assert_eq!(CombineMode::Multiply.combine(0.5, 0.8), 0.4);
+        assert_eq!(CombineMode::Max.combine(0.2, 0.8), 0.8);
+    }
+
+    #[test]
+    fn rolling_resistance_gets_combined_as_well() {
+        let sticky = DEFAULT
+            .rolling_resistance(0.4)
+            .rolling_combine(CombineMode::Max);
+        let smooth = DEFAULT.rolling_resistance(0.1);
+
+        let (_, _, rolling_resistance) = sticky.combine(&smooth);
+        assert_eq!(rolling_resistance, 0.4);
+    }
+
+    #[test]
+    fn material_pairs_override_combined_values() {
+        let rubber = Material::new(1., 0.8, 0.9, "rubber");
+
+        let combined = bounce_height(&mut world(), rubber.clone());
+
+        let mut world = world();
+        world.set_material_pair("rubber", "default", MaterialPair::new(0.8, 0.));
+        let overridden = bounce_height(&mut world, rubber);
+
+        assert!(combined > 0.8, "bounced up to {combined}");
+        assert!(overridden < 0.35, "bounced up to {overridden}");
+
+        // the order of the names does not matter
+        assert!(world.remove_material_pair("default", "rubber").is_some());
+        assert!(world.remove_material_pair("rubber", "default").is_none());
+    }
+}
@@ -1,4 +1,6 @@
 pub mod attractor;
 pub mod body;
 pub mod material;
+pub mod ragdoll;
+pub mod rope;
 pub mod transform;
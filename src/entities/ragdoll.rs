@@ -0,0 +1,284 @@
+//! Humanoid ragdoll made out of OBB limbs, a torso and a circular head.<br>
+//! All parts are sized relative to a single height and connected by angle limited revolute joints.<br>
+//! The parts share a negative collision group, so they never collide with each other.
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, FRAC_PI_6, PI};
+
+use crate::{
+    entities::{body::Body, material::Material},
+    environment::world::World,
+    joints::{Joint, RevoluteJoint},
+    math::Vector2,
+};
+
+// proportions relative to the height, from the feet upwards
+const LOWER_LEG: (f32, f32) = (0.065, 0.25);
+const UPPER_LEG: (f32, f32) = (0.08, 0.25);
+const TORSO: (f32, f32) = (0.2, 0.34);
+const NECK: f32 = 0.02;
+const HEAD_RADIUS: f32 = 0.07;
+const UPPER_ARM: (f32, f32) = (0.05, 0.18);
+const LOWER_ARM: (f32, f32) = (0.045, 0.17);
+const HIP_OFFSET: f32 = 0.05;
+const SHOULDER_OFFSET: f32 = 0.13;
+const SHOULDER_DROP: f32 = 0.02;
+
+#[derive(Clone, Debug)]
+pub struct Ragdoll {
+    pub x: f32,
+    pub y: f32,
+    pub height: f32,
+    pub material: Material,
+}
+
+/// Indices of everything a ragdoll added to the world.<br>
+/// Paired limbs and joints are ordered [left, right].
+#[derive(Clone, Debug, Default)]
+pub struct RagdollHandles {
+    pub head: usize,
+    pub torso: usize,
+    pub upper_arms: [usize; 2],
+    pub lower_arms: [usize; 2],
+    pub upper_legs: [usize; 2],
+    pub lower_legs: [usize; 2],
+
+    pub neck: usize,
+    pub shoulders: [usize; 2],
+    pub elbows: [usize; 2],
+    pub hips: [usize; 2],
+    pub knees: [usize; 2],
+
+    pub collision_group: i32,
+}
+
+impl RagdollHandles {
+    pub fn parts(&self) -> Vec<usize> {
+        let mut parts = vec![self.head, self.torso];
+        parts.extend(self.upper_arms);
+        parts.extend(self.lower_arms);
+        parts.extend(self.upper_legs);
+        parts.extend(self.lower_legs);
+        parts
+    }
+
+    pub fn joints(&self) -> Vec<usize> {
+        let mut joints = vec![self.neck];
+        joints.extend(self.shoulders);
+        joints.extend(self.elbows);
+        joints.extend(self.hips);
+        joints.extend(self.knees);
+        joints
+    }
+}
+
+impl Ragdoll {
+    /// Creates a standing ragdoll with its feet at the given location.
+    pub fn new(x: f32, y: f32, height: f32, material: Material) -> Ragdoll {
+        Ragdoll {
+            x,
+            y,
+            height,
+            material,
+        }
+    }
+}
+
+impl Ragdoll {
+    pub(crate) fn build(self, world: &mut World) -> Option<RagdollHandles> {
+        if self.height <= 0. {
+            println!("User tried to add ragdoll with height: {}", self.height);
+            return None;
+        }
+
+        let h = self.height;
+        let feet = Vector2::new(self.x, self.y);
+        let group = world.create_collision_group();
+
+        let add_part = |world: &mut World, mut part: Body| {
+            part.filter.group = group;
+            world.add_body(part)
+        };
+
+        let limb = |x: f32, bottom: f32, (width, length): (f32, f32)| {
+            let center = feet + Vector2::new(x * h, (bottom + length * 0.5) * h);
+            Body::obb(
+                center.x,
+                center.y,
+                width * h,
+                length * h,
+                self.material.clone(),
+            )
+        };
+
+        let knee_height = LOWER_LEG.1;
+        let hip_height = knee_height + UPPER_LEG.1;
+        let shoulder_height = hip_height + TORSO.1 - SHOULDER_DROP;
+        let elbow_height = shoulder_height - UPPER_ARM.1;
+        let neck_height = hip_height + TORSO.1;
+        let head_center = feet + Vector2::new(0., (neck_height + NECK + HEAD_RADIUS) * h);
+
+        let torso = add_part(world, limb(0., hip_height, TORSO));
+        let head = add_part(
+            world,
+            Body::circle(
+                head_center.x,
+                head_center.y,
+                HEAD_RADIUS * h,
+                self.material.clone(),
+            ),
+        );
+
+        let mut handles = RagdollHandles {
+            head,
+            torso,
+            collision_group: group,
+            ..Default::default()
+        };
+
+        let hinge = |world: &mut World, a_idx: usize, b_idx: usize, anchor: (f32, f32), limit| {
+            let anchor = feet + Vector2::new(anchor.0 * h, anchor.1 * h);
+            let bodies = world.get_bodies();
+            let (lower, upper) = limit;
+
+            let joint = RevoluteJoint::new(&bodies[a_idx], a_idx, &bodies[b_idx], b_idx, anchor)
+                .limit(lower, upper);
+
+            world.add_joint(Joint::Revolute(joint))
+        };
+
+        handles.neck = hinge(
+            world,
+            torso,
+            head,
+            (0., neck_height),
+            (-FRAC_PI_6, FRAC_PI_6),
+        )?;
+
+        // left side first, limits of the right side are mirrored
+        for (side, sign) in [(0, -1.), (1, 1.)] {
+            let mirrored = |lower: f32, upper: f32| {
+                if sign < 0. {
+                    (-upper, -lower)
+                } else {
+                    (lower, upper)
+                }
+            };
+
+            let shoulder_x = sign * SHOULDER_OFFSET;
+            let hip_x = sign * HIP_OFFSET;
+
+            let upper_arm = add_part(world, limb(shoulder_x, elbow_height, UPPER_ARM));
+            let lower_arm = add_part(
+                world,
+                limb(shoulder_x, elbow_height - LOWER_ARM.1, LOWER_ARM),
+            );
+            let upper_leg = add_part(world, limb(hip_x, knee_height, UPPER_LEG));
+            let lower_leg = add_part(world, limb(hip_x, 0., LOWER_LEG));
+
+            handles.upper_arms[side] = upper_arm;
+            handles.lower_arms[side] = lower_arm;
+            handles.upper_legs[side] = upper_leg;
+            handles.lower_legs[side] = lower_leg;
+
+            handles.shoulders[side] = hinge(
+                world,
+                torso,
+                upper_arm,
+                (shoulder_x, shoulder_height),
+                mirrored(-FRAC_PI_4, PI),
+            )?;
+            handles.elbows[side] = hinge(
+                world,
+                upper_arm,
+                lower_arm,
+                (shoulder_x, elbow_height),
+                mirrored(-2.5, 0.),
+            )?;
+            handles.hips[side] = hinge(
+                world,
+                torso,
+                upper_leg,
+                (hip_x, hip_height),
+                mirrored(-FRAC_PI_6, FRAC_PI_2),
+            )?;
+            handles.knees[side] = hinge(
+                world,
+                upper_leg,
+                lower_leg,
+                (hip_x, knee_height),
+                mirrored(0., 2.4),
+            )?;
+        }
+
+        Some(handles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entities::material::DEFAULT, test_utils::world};
+
+    #[test]
+    fn ragdolls_get_their_own_collision_group() {
+        let mut world = World::new(60., 10.);
+        let first = world
+            .add_ragdoll(Ragdoll::new(0., 0., 1.8, DEFAULT))
+            .unwrap();
+        let second = world
+            .add_ragdoll(Ragdoll::new(2., 0., 1.8, DEFAULT))
+            .unwrap();
+
+        assert_eq!(first.parts().len(), 10);
+        assert_eq!(first.joints().len(), 9);
+        assert!(first.collision_group < 0);
+        assert_ne!(first.collision_group, second.collision_group);
+
+        let bodies = world.get_bodies();
+        assert!(first
+            .parts()
+            .iter()
+            .all(|&idx| bodies[idx].filter.group == first.collision_group));
+        assert!(!bodies[first.torso].can_collide_with(&bodies[first.head]));
+        assert!(bodies[first.torso].can_collide_with(&bodies[second.torso]));
+
+        assert!(world
+            .add_ragdoll(Ragdoll::new(0., 0., 0., DEFAULT))
+            .is_none());
+    }
+
+    #[test]
+    fn collapsing_ragdoll_respects_its_joint_limits() {
+        let mut world = world();
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+
+        let handles = world
+            .add_ragdoll(Ragdoll::new(0., 0.5, 1.8, DEFAULT))
+            .unwrap();
+        let parts = handles.parts();
+
+        for _ in 0..300 {
+            world.update();
+
+            for &joint_idx in &handles.joints() {
+                let Joint::Revolute(j) = &world.get_joints()[joint_idx] else {
+                    unreachable!();
+                };
+                let angle = j.get_angle(world.get_bodies());
+
+                assert!(
+                    angle > j.lower_angle - 0.25 && angle < j.upper_angle + 0.25,
+                    "joint {joint_idx} at {angle}, limits {} {}",
+                    j.lower_angle,
+                    j.upper_angle
+                );
+            }
+        }
+
+        let bodies = world.get_bodies();
+        assert!(parts
+            .iter()
+            .all(|&idx| bodies[idx].transform.location.y > -0.1));
+        assert!(bodies[handles.head].transform.location.y < 1.);
+    }
+}
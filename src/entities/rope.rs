@@ -0,0 +1,258 @@
+//! Rope made out of a chain of thin OBB segments, linked by revolute joints.<br>
+//! Both ends can be attached to existing bodies in the world.<br>
+//! Additional max-distance joints from end to end (and from both ends to every segment)
+//! keep the rope from stretching, even if the links are only resolved with few iterations.
+
+use crate::{
+    entities::{body::Body, material::Material},
+    environment::world::World,
+    joints::{DistanceJoint, Joint, RevoluteJoint},
+    math::Vector2,
+};
+
+#[derive(Clone, Debug)]
+pub struct Rope {
+    pub start: Vector2,
+    pub end: Vector2,
+    pub segment_count: usize,
+    pub thickness: f32,
+    pub material: Material,
+    pub attach_start: Option<usize>,
+    pub attach_end: Option<usize>,
+}
+
+/// Indices of everything a rope added to the world.
+#[derive(Clone, Debug, Default)]
+pub struct RopeHandles {
+    /// Segment bodies, ordered from start to end.
+    pub segments: Vec<usize>,
+    /// Revolute joints, ordered from start to end, including the ones to attached bodies.
+    pub links: Vec<usize>,
+    /// Distance joint from start to end.
+    pub max_distance: usize,
+    /// Distance joints from both ends to the individual segments.
+    pub tethers: Vec<usize>,
+}
+
+impl Rope {
+    pub fn new(
+        start: Vector2,
+        end: Vector2,
+        segment_count: usize,
+        thickness: f32,
+        material: Material,
+    ) -> Rope {
+        Rope {
+            start,
+            end,
+            segment_count,
+            thickness,
+            material,
+            attach_start: None,
+            attach_end: None,
+        }
+    }
+
+    /// Attaches the start of the rope to the body with the given index.
+    pub fn attach_start(mut self, body_idx: usize) -> Self {
+        self.attach_start = Some(body_idx);
+        self
+    }
+
+    /// Attaches the end of the rope to the body with the given index.
+    pub fn attach_end(mut self, body_idx: usize) -> Self {
+        self.attach_end = Some(body_idx);
+        self
+    }
+}
+
+impl Rope {
+    pub(crate) fn build(self, world: &mut World) -> Option<RopeHandles> {
+        let direction = self.end - self.start;
+        let length = direction.len();
+        let body_count = world.get_bodies().len();
+
+        let invalid_attachment = [self.attach_start, self.attach_end]
+            .into_iter()
+            .flatten()
+            .any(|idx| idx >= body_count);
+
+        if self.segment_count == 0 || length == 0. || self.thickness <= 0. || invalid_attachment {
+            println!("User tried to add invalid rope: {:?}", self);
+            return None;
+        }
+
+        let mut handles = RopeHandles::default();
+
+        let step = direction * (1. / self.segment_count as f32);
+        let segment_length = length / self.segment_count as f32;
+        let angle = direction.y.atan2(direction.x);
+
+        for idx in 0..self.segment_count {
+            let center = self.start + step * (idx as f32 + 0.5);
+
+            let mut segment = Body::obb(
+                center.x,
+                center.y,
+                segment_length,
+                self.thickness,
+                self.material.clone(),
+            );
+            segment.rotate_fixed_angle(angle);
+
+            handles.segments.push(world.add_body(segment));
+        }
+
+        let mut link = |world: &mut World, a_idx: usize, b_idx: usize, anchor: Vector2| {
+            let bodies = world.get_bodies();
+            let joint = RevoluteJoint::new(&bodies[a_idx], a_idx, &bodies[b_idx], b_idx, anchor);
+
+            handles.links.push(world.add_joint(Joint::Revolute(joint))?);
+            Some(())
+        };
+
+        let first = handles.segments[0];
+        let last = handles.segments[self.segment_count - 1];
+
+        if let Some(a_idx) = self.attach_start {
+            link(world, a_idx, first, self.start)?;
+        }
+
+        for idx in 1..self.segment_count {
+            let (a_idx, b_idx) = (handles.segments[idx - 1], handles.segments[idx]);
+            link(world, a_idx, b_idx, self.start + step * idx as f32)?;
+        }
+
+        if let Some(b_idx) = self.attach_end {
+            link(world, last, b_idx, self.end)?;
+        }
+
+        let a_idx = self.attach_start.unwrap_or(first);
+        let b_idx = self.attach_end.unwrap_or(last);
+
+        let max_distance = |world: &mut World, a_idx, b_idx, anchors: (Vector2, Vector2)| {
+            let bodies = world.get_bodies();
+            let max_length = (anchors.1 - anchors.0).len();
+
+            let joint = DistanceJoint::new(
+                &bodies[a_idx],
+                a_idx,
+                &bodies[b_idx],
+                b_idx,
+                anchors.0,
+                anchors.1,
+            )
+            .range(0., max_length)
+            .collide_connected(true);
+
+            world.add_joint(Joint::Distance(joint))
+        };
+
+        handles.max_distance = max_distance(world, a_idx, b_idx, (self.start, self.end))?;
+
+        // tethers from both ends to every segment, so the links cannot stretch either
+        for idx in 1..self.segment_count {
+            let segment_idx = handles.segments[idx];
+            let far_end = self.start + step * (idx + 1) as f32;
+            let joint_idx = max_distance(world, a_idx, segment_idx, (self.start, far_end))?;
+            handles.tethers.push(joint_idx);
+
+            let segment_idx = handles.segments[self.segment_count - 1 - idx];
+            let near_end = self.start + step * (self.segment_count - 1 - idx) as f32;
+            let joint_idx = max_distance(world, b_idx, segment_idx, (self.end, near_end))?;
+            handles.tethers.push(joint_idx);
+        }
+
+        Some(handles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entities::material::DEFAULT, joints::to_world, test_utils::world};
+
+    /// Largest distance between the anchors of two linked bodies.
+    fn max_link_gap(world: &World, handles: &RopeHandles) -> f32 {
+        let bodies = world.get_bodies();
+
+        handles
+            .links
+            .iter()
+            .map(|&joint_idx| {
+                let Joint::Revolute(j) = &world.get_joints()[joint_idx] else {
+                    unreachable!();
+                };
+                let (a, b) = (&bodies[j.a_idx], &bodies[j.b_idx]);
+                let anchor_a = a.transform.location + to_world(a, j.local_anchor_a);
+                let anchor_b = b.transform.location + to_world(b, j.local_anchor_b);
+
+                (anchor_b - anchor_a).len()
+            })
+            .fold(0., f32::max)
+    }
+
+    #[test]
+    fn swinging_rope_stays_linked() {
+        let mut world = world();
+        let ceiling = world.add_body(Body::platform_circle(0., 0., 0.1, DEFAULT));
+        let weight = world.add_body(Body::aabb(10., 0., 0.5, 0.5, DEFAULT));
+
+        let rope = Rope::new(
+            Vector2::new(0., 0.),
+            Vector2::new(10., 0.),
+            10,
+            0.1,
+            DEFAULT,
+        )
+        .attach_start(ceiling)
+        .attach_end(weight);
+        let handles = world.add_rope(rope).unwrap();
+
+        assert_eq!(handles.links.len(), 11);
+
+        for _ in 0..600 {
+            world.update();
+
+            let gap = max_link_gap(&world, &handles);
+            assert!(gap < 0.05, "links are {gap} apart");
+
+            let stretch = world.get_bodies()[weight].transform.location.len();
+            assert!(stretch < 10.05, "rope stretched to {stretch}");
+        }
+    }
+
+    #[test]
+    fn free_rope_stays_linked_when_landing() {
+        let mut world = world();
+        world.add_body(Body::platform_rectangle_aabb(0., -20., 100., 1., DEFAULT));
+
+        let rope = Rope::new(Vector2::new(0., 0.), Vector2::new(5., 0.), 5, 0.1, DEFAULT);
+        let handles = world.add_rope(rope).unwrap();
+
+        assert_eq!(handles.tethers.len(), 8);
+
+        for _ in 0..600 {
+            world.update();
+
+            let gap = max_link_gap(&world, &handles);
+            assert!(gap < 0.05, "links are {gap} apart");
+        }
+    }
+
+    #[test]
+    fn invalid_ropes_are_rejected() {
+        let mut world = world();
+        let start = Vector2::new(0., 0.);
+
+        let unknown_attachment =
+            Rope::new(start, Vector2::new(1., 0.), 4, 0.1, DEFAULT).attach_end(3);
+        let no_length = Rope::new(start, start, 4, 0.1, DEFAULT);
+        let no_segments = Rope::new(start, Vector2::new(1., 0.), 0, 0.1, DEFAULT);
+
+        assert!(world.add_rope(unknown_attachment).is_none());
+        assert!(world.add_rope(no_length).is_none());
+        assert!(world.add_rope(no_segments).is_none());
+        assert!(world.get_bodies().is_empty());
+    }
+}
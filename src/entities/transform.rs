@@ -8,6 +8,7 @@ pub struct Transform {
     pub location: Vector2,
     pub velocity: Vector2,
     pub angular_velocity: f32,
+    pub rotation: f32,
 }
 
 impl Transform {
@@ -16,6 +17,7 @@ impl Transform {
             location: Vector2::new(x, y),
             velocity: ZERO,
             angular_velocity: 0.0,
+            rotation: 0.0,
         }
     }
 }
@@ -24,8 +26,8 @@ impl Display for Transform {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Location: {}\nVelocity: {}\nAngle Velocity: {}\n",
-            self.location, self.velocity, self.angular_velocity,
+            "Location: {}\nVelocity: {}\nAngle Velocity: {}\nRotation: {}\n",
+            self.location, self.velocity, self.angular_velocity, self.rotation,
         )
     }
 }
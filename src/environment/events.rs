@@ -0,0 +1,174 @@
+//! Events the world collects during updates, drained by the user after each update.
+
+use crate::math::{vector2::ZERO, Vector2};
+
+/// Overlap changes between a sensor body and any other body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SensorEvent {
+    BeginOverlap { sensor: usize, visitor: usize },
+    EndOverlap { sensor: usize, visitor: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContactPhase {
+    /// The bodies started touching this step.
+    Begin,
+    /// The bodies were already touching in the previous step.
+    Persist,
+    /// The bodies stopped touching, the event carries no contact data.
+    End,
+}
+
+/// Contact between two bodies, a_idx is always the smaller index.<br>
+/// The normal points from body a to body b, impulses are the totals the solver applied during the step.
+#[derive(Clone, Debug)]
+pub struct ContactEvent {
+    pub phase: ContactPhase,
+    pub a_idx: usize,
+    pub b_idx: usize,
+    pub normal: Vector2,
+    pub points: Vec<Vector2>,
+    pub normal_impulse: f32,
+    pub tangent_impulse: f32,
+}
+
+impl ContactEvent {
+    pub(crate) fn ended(a_idx: usize, b_idx: usize) -> ContactEvent {
+        ContactEvent {
+            phase: ContactPhase::End,
+            a_idx,
+            b_idx,
+            normal: ZERO,
+            points: Vec::new(),
+            normal_impulse: 0.,
+            tangent_impulse: 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{body::Body, material::DEFAULT},
+        test_utils::{run, sensor, sleeping_box, world},
+    };
+
+    #[test]
+    fn sensors_report_overlaps_without_blocking() {
+        let mut world = world();
+        let sensor = sensor(&mut world, 0., 0., 2.);
+        let ball = world.add_body(Body::circle(0., 3., 0.3, DEFAULT));
+
+        let mut events = Vec::new();
+
+        for _ in 0..90 {
+            world.update();
+            events.extend(world.drain_sensor_events());
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                SensorEvent::BeginOverlap {
+                    sensor,
+                    visitor: ball
+                },
+                SensorEvent::EndOverlap {
+                    sensor,
+                    visitor: ball
+                },
+            ]
+        );
+        assert!(world.get_bodies()[ball].transform.location.y < -1.3);
+    }
+
+    #[test]
+    fn sleeping_bodies_stay_inside_sensors() {
+        let (mut world, _, body) = sleeping_box();
+        let sensor = sensor(&mut world, 0., 0.5, 2.);
+
+        world.update();
+        assert_eq!(
+            world.drain_sensor_events(),
+            vec![SensorEvent::BeginOverlap {
+                sensor,
+                visitor: body
+            }]
+        );
+
+        run(&mut world, 30);
+
+        assert!(world.get_bodies()[body].is_sleeping());
+        assert!(world.drain_sensor_events().is_empty());
+    }
+
+    fn contact_phases(events: &[ContactEvent], a_idx: usize, b_idx: usize) -> Vec<ContactPhase> {
+        events
+            .iter()
+            .filter(|e| e.a_idx == a_idx && e.b_idx == b_idx)
+            .map(|e| e.phase)
+            .collect()
+    }
+
+    #[test]
+    fn contacts_begin_persist_and_end() {
+        let mut world = world();
+        let ground = world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let block = world.add_body(Body::aabb(0., 0.5, 1., 1., DEFAULT));
+
+        let mut events = Vec::new();
+
+        for _ in 0..10 {
+            world.update();
+            events.extend(world.drain_contact_events());
+        }
+
+        let begin = events
+            .iter()
+            .find(|e| e.phase == ContactPhase::Begin)
+            .unwrap();
+        assert_eq!((begin.a_idx, begin.b_idx), (ground, block));
+        assert!(begin.normal.y > 0.9);
+        assert!(begin.normal_impulse > 0.);
+        assert!(!begin.points.is_empty());
+
+        world.get_body_mut(block).unwrap().transform.velocity = Vector2::new(0., 10.);
+        world.update();
+        world.update();
+        events.extend(world.drain_contact_events());
+
+        let phases = contact_phases(&events, ground, block);
+        assert_eq!(phases.first(), Some(&ContactPhase::Begin));
+        assert_eq!(phases.last(), Some(&ContactPhase::End));
+        assert!(phases.contains(&ContactPhase::Persist));
+    }
+
+    #[test]
+    fn removing_a_body_ends_its_contacts_and_overlaps() {
+        let mut world = world();
+        let ground = world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let sensor = sensor(&mut world, 0., 0.5, 2.);
+        let body = world.add_body(Body::aabb(0., 0.5, 1., 1., DEFAULT));
+
+        run(&mut world, 10);
+        world.drain_contact_events();
+        world.drain_sensor_events();
+
+        world.remove_body(body);
+
+        let contacts = world.drain_contact_events();
+        assert_eq!(contact_phases(&contacts, ground, body), [ContactPhase::End]);
+        assert_eq!(
+            world.drain_sensor_events(),
+            [SensorEvent::EndOverlap {
+                sensor,
+                visitor: body
+            }]
+        );
+
+        run(&mut world, 10);
+        assert!(world.drain_contact_events().is_empty());
+        assert!(world.drain_sensor_events().is_empty());
+    }
+}
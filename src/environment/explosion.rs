@@ -0,0 +1,136 @@
+//! Radial impulses pushing bodies away from a center.<br>
+//! Each body gets hit at its closest point to the center, so off-center hits also make it spin.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Falloff {
+    /// Full impulse at the center, none at the edge of the radius.
+    #[default]
+    Linear,
+    /// Inverse square of the distance relative to the radius, s = distance / radius.<br>
+    /// Scaled by (1 - s²) / (1 + 8s²), so it drops to a quarter at half the radius and reaches
+    /// zero at the edge, the same for every radius.
+    InverseSquare,
+}
+
+impl Falloff {
+    /// Share of the impulse a body receives at the given distance from the center.
+    pub fn scale(&self, distance: f32, radius: f32) -> f32 {
+        if distance > radius {
+            return 0.;
+        }
+
+        match self {
+            Falloff::Linear => 1. - distance / radius,
+            Falloff::InverseSquare => {
+                let s = distance / radius;
+                (1. - s * s) / (1. + 8. * s * s)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{body::Body, material::DEFAULT},
+        environment::world::World,
+        joints::{DistanceJoint, Joint},
+        math::{vector2::ZERO, Vector2},
+        test_utils::run,
+    };
+
+    #[test]
+    fn falloff_decreases_with_distance() {
+        assert_eq!(Falloff::Linear.scale(0., 4.), 1.);
+        assert_eq!(Falloff::Linear.scale(3., 4.), 0.25);
+        assert_eq!(Falloff::InverseSquare.scale(0., 4.), 1.);
+        assert_eq!(Falloff::InverseSquare.scale(2., 4.), 0.25);
+        assert_eq!(Falloff::InverseSquare.scale(4., 4.), 0.);
+
+        // nothing beyond the radius, no matter the falloff
+        assert_eq!(Falloff::Linear.scale(4.5, 4.), 0.);
+        assert_eq!(Falloff::InverseSquare.scale(4.5, 4.), 0.);
+    }
+
+    #[test]
+    fn inverse_square_falloff_scales_with_the_radius() {
+        let small = Falloff::InverseSquare.scale(1., 2.);
+        let large = Falloff::InverseSquare.scale(10., 20.);
+
+        assert!((small - large).abs() < 0.0001, "{small} vs {large}");
+    }
+
+    #[test]
+    fn explosions_push_bodies_in_range_away() {
+        let mut world = World::new(60., 10.);
+        let near = world.add_body(Body::aabb(1.5, 0., 1., 1., DEFAULT));
+        let far = world.add_body(Body::aabb(-3.5, 0., 1., 1., DEFAULT));
+        let outside = world.add_body(Body::aabb(0., 6.5, 1., 1., DEFAULT));
+        let wall = world.add_body(Body::platform_rectangle_aabb(0., -3., 1., 1., DEFAULT));
+
+        world.explode(ZERO, 5., 10., Falloff::Linear);
+
+        let bodies = world.get_bodies();
+        let (near, far) = (&bodies[near].transform, &bodies[far].transform);
+
+        assert!(near.velocity.x > 0. && far.velocity.x < 0.);
+        assert!(near.velocity.x > -far.velocity.x * 1.5);
+        assert_eq!(bodies[outside].transform.velocity, ZERO);
+        assert_eq!(bodies[wall].transform.velocity, ZERO);
+    }
+
+    #[test]
+    fn off_center_explosions_spin_bodies() {
+        let mut world = World::new(60., 10.);
+        let centered = world.add_body(Body::obb(2., 0., 1., 1., DEFAULT));
+        let mut tilted = Body::obb(0., 2., 1., 1., DEFAULT);
+        tilted.rotate_fixed_angle(0.3);
+        let tilted = world.add_body(tilted);
+
+        world.explode(ZERO, 5., 10., Falloff::InverseSquare);
+
+        let bodies = world.get_bodies();
+        assert!(bodies[centered].transform.angular_velocity.abs() < 0.001);
+        assert!(bodies[tilted].transform.angular_velocity.abs() > 0.1);
+    }
+
+    #[test]
+    fn occluded_explosions_spare_shielded_bodies() {
+        let mut world = World::new(60., 10.);
+        world.add_body(Body::platform_rectangle_aabb(2., 0., 0.5, 4., DEFAULT));
+        let shielded = world.add_body(Body::aabb(4., 0., 1., 1., DEFAULT));
+        let exposed = world.add_body(Body::aabb(-4., 0., 1., 1., DEFAULT));
+
+        world.explode_occluded(ZERO, 6., 10., Falloff::Linear);
+
+        let bodies = world.get_bodies();
+        assert_eq!(bodies[shielded].transform.velocity, ZERO);
+        assert!(bodies[exposed].transform.velocity.x < 0.);
+
+        world.explode(ZERO, 6., 10., Falloff::Linear);
+        assert!(world.get_bodies()[shielded].transform.velocity.x > 0.);
+    }
+
+    #[test]
+    fn explosions_wake_the_island_of_hit_bodies() {
+        let mut world = World::new(60., 10.);
+        let (a, b) = (
+            Body::circle(0., 0., 0.5, DEFAULT),
+            Body::circle(3., 0., 0.5, DEFAULT),
+        );
+        let joint = DistanceJoint::new(&a, 0, &b, 1, a.transform.location, b.transform.location);
+        let (hit, jointed) = (world.add_body(a), world.add_body(b));
+        world.add_joint(Joint::Distance(joint));
+
+        run(&mut world, 60);
+        assert!(world.get_bodies()[jointed].is_sleeping());
+
+        // only reaches the first ball
+        world.explode(Vector2::new(-1., 0.), 1., 5., Falloff::Linear);
+
+        let bodies = world.get_bodies();
+        assert!(bodies[hit].transform.velocity.x > 0.);
+        assert!(!bodies[jointed].is_sleeping());
+    }
+}
@@ -0,0 +1,192 @@
+//! Simulation islands, groups of dynamic bodies that are connected via contacts or joints.<br>
+//! Islands are the unit that falls asleep and wakes up: if one body in an island moves,
+//! every other body in it might be affected as well.<br>
+//! Static bodies never join islands, otherwise everything lying on the ground would be one island.
+
+/// Disjoint-set (union-find) over body indices.
+pub(crate) struct Islands {
+    parents: Vec<usize>,
+}
+
+impl Islands {
+    pub(crate) fn new(body_count: usize) -> Islands {
+        Islands {
+            parents: (0..body_count).collect(),
+        }
+    }
+
+    /// Representative body of the island the given body belongs to.
+    pub(crate) fn find(&mut self, body_idx: usize) -> usize {
+        let mut root = body_idx;
+
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // path compression
+        let mut idx = body_idx;
+        while self.parents[idx] != root {
+            let next = self.parents[idx];
+            self.parents[idx] = root;
+            idx = next;
+        }
+
+        root
+    }
+
+    pub(crate) fn union(&mut self, a_idx: usize, b_idx: usize) {
+        let a_root = self.find(a_idx);
+        let b_root = self.find(b_idx);
+
+        if a_root != b_root {
+            self.parents[b_root] = a_root;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{
+            attractor::{Attractor, AttractorType},
+            body::Body,
+            material::DEFAULT,
+        },
+        environment::world::World,
+        test_utils::{run, sleeping_box, world},
+    };
+
+    #[test]
+    fn union_joins_islands_transitively() {
+        let mut islands = Islands::new(5);
+
+        islands.union(0, 1);
+        islands.union(3, 1);
+
+        assert_eq!(islands.find(0), islands.find(3));
+        assert_ne!(islands.find(0), islands.find(2));
+        assert_eq!(islands.find(4), 4);
+    }
+
+    #[test]
+    fn resting_bodies_fall_asleep_and_stay_in_place() {
+        let (mut world, _, body) = sleeping_box();
+        let location = world.get_bodies()[body].transform.location;
+
+        run(&mut world, 60);
+
+        assert!(world.get_bodies()[body].is_sleeping());
+        assert_eq!(world.get_bodies()[body].transform.location, location);
+    }
+
+    #[test]
+    fn island_ids_are_body_indices() {
+        let mut world = world();
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        world.add_body(Body::aabb(-3., 0.5, 1., 1., DEFAULT));
+        world.add_body(Body::aabb(3., 0.5, 1., 1., DEFAULT));
+        world.add_body(Body::aabb(3., 1.5, 1., 1., DEFAULT));
+
+        run(&mut world, 600);
+
+        let bodies = world.get_bodies();
+        assert!(bodies.iter().all(|b| b.island < bodies.len()));
+        assert_ne!(bodies[1].island, bodies[2].island);
+        assert_eq!(bodies[2].island, bodies[3].island);
+    }
+
+    #[test]
+    fn bodies_never_sleep_if_sleeping_is_disabled() {
+        let mut world = world();
+        world.set_sleeping_enabled(false);
+        world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let body = world.add_body(Body::aabb(0., 0.5, 1., 1., DEFAULT));
+
+        run(&mut world, 180);
+
+        assert!(!world.get_bodies()[body].is_sleeping());
+    }
+
+    #[test]
+    fn slow_bodies_stay_awake_below_tighter_tolerances() {
+        let mut world = World::new(60., 10.);
+        let body = world.add_body(Body::circle(0., 0., 0.5, DEFAULT));
+        world.get_body_mut(body).unwrap().transform.velocity.x = 0.05;
+
+        run(&mut world, 60);
+        assert!(world.get_bodies()[body].is_sleeping());
+
+        world.set_sleep_tolerances(0.01, 0.01);
+        world.get_body_mut(body).unwrap().transform.velocity.x = 0.05;
+
+        run(&mut world, 60);
+        assert!(!world.get_bodies()[body].is_sleeping());
+        assert_eq!(world.get_sleep_tolerances(), (0.01, 0.01));
+    }
+
+    #[test]
+    fn removing_static_ground_wakes_the_bodies_on_it() {
+        let (mut world, ground, body) = sleeping_box();
+
+        world.remove_body(ground);
+        // the box was added after the ground, so its index shifted down
+        let body = body - 1;
+        run(&mut world, 30);
+
+        assert!(!world.get_bodies()[body].is_sleeping());
+        assert!(world.get_bodies()[body].transform.location.y < 0.);
+    }
+
+    #[test]
+    fn moving_static_ground_wakes_the_bodies_on_it() {
+        let (mut world, ground, body) = sleeping_box();
+
+        world.get_body_mut(ground).unwrap().transform.location.y -= 2.;
+        run(&mut world, 60);
+
+        assert!(!world.get_bodies()[body].is_sleeping());
+        assert!(world.get_bodies()[body].transform.location.y < -1.);
+    }
+
+    #[test]
+    fn hitting_a_sleeping_island_wakes_it() {
+        let (mut world, _, body) = sleeping_box();
+        let ball = world.add_body(Body::circle(0., 3., 0.3, DEFAULT));
+
+        let mut woken = false;
+
+        for _ in 0..60 {
+            world.update();
+            woken |= !world.get_bodies()[body].is_sleeping();
+        }
+
+        assert!(woken);
+        assert!(world.get_bodies()[ball].transform.location.y < 2.);
+    }
+
+    #[test]
+    fn adding_an_attractor_wakes_sleeping_bodies() {
+        let (mut world, _, body) = sleeping_box();
+
+        let attractor = Attractor::new(0., 10., 1., AttractorType::Global, None).mass(1e6);
+        world.add_attractor(attractor);
+        world.update();
+
+        assert!(!world.get_bodies()[body].is_sleeping());
+        assert!(world.get_bodies()[body].transform.velocity.y > 0.);
+    }
+
+    #[test]
+    fn removing_an_attractor_wakes_sleeping_bodies() {
+        let (mut world, _, body) = sleeping_box();
+
+        world.add_attractor(Attractor::new(0., 10., 1., AttractorType::Global, None));
+        run(&mut world, 180);
+        assert!(world.get_bodies()[body].is_sleeping());
+
+        world.remove_attractor(0);
+
+        assert!(!world.get_bodies()[body].is_sleeping());
+    }
+}
@@ -1,2 +1,5 @@
+pub mod events;
+pub mod explosion;
 pub mod force;
+pub mod island;
 pub mod world;
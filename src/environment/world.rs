@@ -1,42 +1,103 @@
 //! This is the main entrypoint for any program using this library.<br>
 //! Simply define a world, fill it with entities, and call world.update() to advance a discrete physics step.<br>
+//! Alternatively call world.advance(frame_time), which runs as many fixed steps as the passed time requires.<br>
+//! Bodies that rest long enough fall asleep together with their island and are not simulated until woken up.<br>
 //! <br>Simulated physics world that contains:
 //! - bodies
+//! - joints
 //! - attractors
-//! - forces<br>
-//! <br>
+//! - forces
 
-use std::{collections::HashMap, fmt::Display, mem, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    mem,
+    time::Instant,
+};
 
 use crate::{
     collision::{
-        correct_position, detect_collision, detection::hitboxes_collide, resolve_collision,
-        Manifold,
+        correct_position, detect_collision,
+        detection::hitboxes_collide,
+        grid::SpatialGrid,
+        manifold::rolling_radius,
+        raycast::raycast_body,
+        resolve_collision,
+        separation::separation,
+        shape_cast::{shape_cast_body, swept_hitbox},
+        soft::{
+            apply_restitution, prepare_soft_contact, solve_soft_contact, warm_start_soft_contact,
+            CachedContacts, Origin, Softness,
+        },
+        CollisionFilter, ContactModifier, Hitbox, Manifold, RaycastHit, Separation, ShapeCastHit,
+        SolverSettings, SubstepSettings,
     },
     entities::{
         attractor::Attractor,
-        body::{Body, BodyType::*},
+        body::{Body, BodyType::Static},
+        material::MaterialPair,
+        ragdoll::{Ragdoll, RagdollHandles},
+        rope::{Rope, RopeHandles},
+    },
+    environment::{
+        events::{ContactEvent, ContactPhase, SensorEvent},
+        explosion::Falloff,
+        force::Force,
+        island::Islands,
     },
-    environment::force::Force,
+    joints::Joint,
     math::{vector2::ZERO, Vector2},
+    shapes::{Circle, Shape, AABB},
 };
 
 #[derive(Default)]
 pub struct World {
     bodies: Vec<Body>,
+    joints: Vec<Joint>,
     forces: Vector2,
     attractors: Vec<Attractor>,
     manifolds: HashMap<(usize, usize), Manifold>,
+    /// Contacts at the end of the last sub-stepped step, warm start the next one.
+    cached_contacts: HashMap<(usize, usize), CachedContacts>,
     possible_collisions: Vec<(usize, usize)>,
     pub collision_points: Vec<Vector2>,
     tick_rate: f32,
     delta_time: f32,
-    collision_precision: usize,
     pixel_to_meter: f32,
     inv_pixel_to_meter: f32,
     last_step_duration: f32,
+    collision_groups: i32,
+    active_joints: Vec<usize>,
+    sleeping_enabled: bool,
+    time_to_sleep: f32,
+    linear_sleep_tolerance: f32,
+    angular_sleep_tolerance: f32,
+    sensor_overlaps: HashSet<(usize, usize)>,
+    sensor_events: Vec<SensorEvent>,
+    touching: HashSet<(usize, usize)>,
+    contact_events: Vec<ContactEvent>,
+    contact_modifier: Option<Box<dyn ContactModifier>>,
+    one_way_passing: HashSet<(usize, usize)>,
+    material_pairs: HashMap<(&'static str, &'static str), MaterialPair>,
+    solver_settings: SolverSettings,
+    grid: SpatialGrid,
+    /// Bodies were added, removed or moved since the grid was built.
+    grid_outdated: bool,
+    /// Real time that has not been simulated yet.
+    accumulator: f32,
+    max_steps: usize,
 }
 
+/// Seconds an island has to rest before it falls asleep.
+const DEFAULT_TIME_TO_SLEEP: f32 = 0.5;
+
+/// Velocities (m/s and rad/s) below which a body counts as resting.
+const DEFAULT_LINEAR_SLEEP_TOLERANCE: f32 = 0.08;
+const DEFAULT_ANGULAR_SLEEP_TOLERANCE: f32 = 0.08;
+
+/// Steps a single call of World::advance may execute at most.
+const DEFAULT_MAX_STEPS: usize = 5;
+
 impl World {
     pub fn new(tick_rate: f32, pixel_to_meter: f32) -> World {
         World {
@@ -44,35 +105,147 @@ impl World {
             delta_time: 1.0 / tick_rate,
             pixel_to_meter,
             inv_pixel_to_meter: 1.0 / pixel_to_meter,
-            collision_precision: 1,
+            sleeping_enabled: true,
+            time_to_sleep: DEFAULT_TIME_TO_SLEEP,
+            linear_sleep_tolerance: DEFAULT_LINEAR_SLEEP_TOLERANCE,
+            angular_sleep_tolerance: DEFAULT_ANGULAR_SLEEP_TOLERANCE,
+            max_steps: DEFAULT_MAX_STEPS,
+            solver_settings: SolverSettings::default(),
             ..Default::default()
         }
     }
 }
 
 impl World {
-    pub fn add_body(&mut self, body: Body) {
+    /// Returns the index of the added body, which is used to reference it in joints.
+    pub fn add_body(&mut self, mut body: Body) -> usize {
+        body.previous_transform = body.transform.clone();
         self.bodies.push(body);
+        self.grid_outdated = true;
+        self.bodies.len() - 1
     }
 
+    /// Removes the body and all joints attached to it, its contacts and sensor overlaps end.<br>
+    /// Indices of bodies added after the removed one shift down by one, end events still use the old ones.
     pub fn remove_body(&mut self, body_idx: usize) -> Option<Body> {
         if body_idx >= self.bodies.len() {
             return None;
         }
 
+        // bodies resting on the removed body have to react
+        self.wake_touching(body_idx);
+
+        while let Some(joint_idx) = self
+            .joints
+            .iter()
+            .position(|j| j.body_indices().contains(&body_idx))
+        {
+            self.remove_joint(joint_idx);
+        }
+
+        for joint in &mut self.joints {
+            joint.body_removed(body_idx);
+        }
+
+        self.end_pairs_of(body_idx);
+
+        // islands are named after one of their bodies, which might have shifted as well
+        for body in self.bodies.iter_mut().filter(|b| b.island > body_idx) {
+            body.island -= 1;
+        }
+
+        for pairs in [
+            &mut self.sensor_overlaps,
+            &mut self.touching,
+            &mut self.one_way_passing,
+        ] {
+            *pairs = mem::take(pairs)
+                .into_iter()
+                .filter(|&(a_idx, b_idx)| a_idx != body_idx && b_idx != body_idx)
+                .map(|(a_idx, b_idx)| (shifted(a_idx, body_idx), shifted(b_idx, body_idx)))
+                .collect();
+        }
+
+        self.cached_contacts = mem::take(&mut self.cached_contacts)
+            .into_iter()
+            .filter(|&((a_idx, b_idx), _)| a_idx != body_idx && b_idx != body_idx)
+            .map(|((a_idx, b_idx), c)| ((shifted(a_idx, body_idx), shifted(b_idx, body_idx)), c))
+            .collect();
+
+        self.grid_outdated = true;
         Some(self.bodies.remove(body_idx))
     }
 
+    /// Returns the index of the added joint, which is used to reference it in gear joints.<br>
+    /// Returns None if the joint references bodies or joints that do not exist.
+    pub fn add_joint(&mut self, joint: Joint) -> Option<usize> {
+        let body_count = self.bodies.len();
+        let joint_count = self.joints.len();
+
+        let unknown_body = joint.body_indices().iter().any(|&idx| idx >= body_count);
+        let unknown_joint = matches!(
+            &joint,
+            Joint::Gear(g) if g.joint_1 >= joint_count || g.joint_2 >= joint_count
+        );
+
+        if unknown_body || unknown_joint {
+            println!("User tried to add invalid joint: {}", joint);
+            return None;
+        }
+
+        for body_idx in joint.body_indices() {
+            self.wake(body_idx);
+        }
+
+        self.joints.push(joint);
+        Some(self.joints.len() - 1)
+    }
+
+    /// Removes the joint and all gear joints that depend on it.<br>
+    /// Indices of joints added after the removed one shift down by one.
+    pub fn remove_joint(&mut self, joint_idx: usize) -> Option<Joint> {
+        if joint_idx >= self.joints.len() {
+            return None;
+        }
+
+        let joint = self.joints.remove(joint_idx);
+
+        for j in &mut self.joints {
+            j.joint_removed(joint_idx);
+        }
+
+        while let Some(detached_idx) = self.joints.iter().position(Joint::is_detached) {
+            self.remove_joint(detached_idx);
+        }
+
+        Some(joint)
+    }
+
+    /// Adds all segments and joints of the rope.<br>
+    /// Returns None if the rope has no length, no segments or is attached to unknown bodies.
+    pub fn add_rope(&mut self, rope: Rope) -> Option<RopeHandles> {
+        rope.build(self)
+    }
+
+    /// Adds all parts and joints of the ragdoll, the parts get a new collision group.<br>
+    /// Returns None if the ragdoll has no height.
+    pub fn add_ragdoll(&mut self, ragdoll: Ragdoll) -> Option<RagdollHandles> {
+        ragdoll.build(self)
+    }
+
     pub fn add_force(&mut self, force: Force) {
         self.forces += force.acceleration;
+        self.wake_all();
     }
 
     pub fn remove_force(&mut self, force: Force) {
         self.forces -= force.acceleration;
+        self.wake_all();
     }
 
     pub fn add_attractor(&mut self, attractor: Attractor) {
         self.attractors.push(attractor);
+        self.wake_all();
     }
 
     pub fn remove_attractor(&mut self, attractor_idx: usize) -> Option<Attractor> {
@@ -80,6 +253,7 @@ impl World {
             return None;
         }
 
+        self.wake_all();
         Some(self.attractors.remove(attractor_idx))
     }
 
@@ -101,13 +275,111 @@ impl World {
         }
     }
 
+    /// Ignores impulses below the impulse threshold of the solver settings and wakes the body.
+    pub fn apply_impulse(&mut self, body_idx: usize, impulse: Vector2) {
+        let threshold = self.solver_settings.get_impulse_threshold();
+
+        if let Some(body) = self.bodies.get_mut(body_idx) {
+            body.apply_impulse(impulse, threshold);
+        }
+    }
+
+    /// Pushes all dynamic bodies within the radius away from the center.<br>
+    /// The impulse is the one a body at the center would receive, it decreases with the falloff.
+    pub fn explode(&mut self, center: Vector2, radius: f32, impulse: f32, falloff: Falloff) {
+        self.apply_explosion(center, radius, impulse, falloff, false);
+    }
+
+    /// Like explode, but bodies shielded by static bodies are not affected.
+    pub fn explode_occluded(
+        &mut self,
+        center: Vector2,
+        radius: f32,
+        impulse: f32,
+        falloff: Falloff,
+    ) {
+        self.apply_explosion(center, radius, impulse, falloff, true);
+    }
+
+    fn apply_explosion(
+        &mut self,
+        center: Vector2,
+        radius: f32,
+        impulse: f32,
+        falloff: Falloff,
+        occluded: bool,
+    ) {
+        let probe = Body::probe(Shape::Circle(Circle::new(0.)), center, 0.);
+        let area = Hitbox::new(
+            center - Vector2::new(radius, radius),
+            center + Vector2::new(radius, radius),
+        );
+        let mut impulses = Vec::new();
+
+        for body_idx in self.candidates(&area) {
+            let body = &self.bodies[body_idx];
+
+            if body.body_type == Static || body.is_sensor {
+                continue;
+            }
+
+            let s = separation(&probe, body, &self.solver_settings);
+            let distance = f32::max(s.distance, 0.);
+            let scale = falloff.scale(distance, radius);
+
+            if scale <= 0. || (occluded && self.is_shielded(center, s.point_b, distance)) {
+                continue;
+            }
+
+            impulses.push((body_idx, s.normal * impulse * scale, s.point_b));
+        }
+
+        // waking the whole island, so bodies resting on the pushed ones do not stay asleep
+        for (body_idx, impulse, point) in impulses {
+            self.wake(body_idx);
+            self.bodies[body_idx].apply_impulse_at_point(impulse, point);
+        }
+    }
+
+    /// Checks if a static body lies between the center and the point.
+    fn is_shielded(&self, center: Vector2, point: Vector2, distance: f32) -> bool {
+        self.raycast_all(center, point - center, distance, CollisionFilter::default())
+            .iter()
+            .any(|hit| self.bodies[hit.body_idx].body_type == Static)
+    }
+
+    /// Returns a new negative collision group, bodies in this group never collide with each other.
+    pub fn create_collision_group(&mut self) -> i32 {
+        self.collision_groups -= 1;
+        self.collision_groups
+    }
+
     pub fn clear(&mut self) {
         self.bodies.clear();
+        self.joints.clear();
         self.attractors.clear();
         self.forces = ZERO;
         self.manifolds.clear();
+        self.cached_contacts.clear();
         self.possible_collisions.clear();
         self.collision_points.clear();
+        self.sensor_overlaps.clear();
+        self.sensor_events.clear();
+        self.touching.clear();
+        self.contact_events.clear();
+        self.one_way_passing.clear();
+        self.grid_outdated = true;
+    }
+
+    /// Returns all sensor events since the last call, in the order they happened.
+    pub fn drain_sensor_events(&mut self) -> Vec<SensorEvent> {
+        mem::take(&mut self.sensor_events)
+    }
+
+    /// Returns all contact events since the last call.<br>
+    /// Bodies that fall asleep while touching do not end their contact.
+    pub fn drain_contact_events(&mut self) -> Vec<ContactEvent> {
+        mem::take(&mut self.contact_events)
     }
 }
 
@@ -117,10 +389,44 @@ impl World {
         &self.bodies
     }
 
+    /// Wakes the body and everything touching it, since it might get mutated.<br>
+    /// Queries scan all bodies until the next update, since the body might get moved.
+    pub fn get_body_mut(&mut self, body_idx: usize) -> Option<&mut Body> {
+        if body_idx >= self.bodies.len() {
+            return None;
+        }
+
+        self.wake_touching(body_idx);
+        self.grid_outdated = true;
+        Some(&mut self.bodies[body_idx])
+    }
+
+    pub fn get_joints(&self) -> &Vec<Joint> {
+        &self.joints
+    }
+
     pub fn get_attractors(&self) -> &Vec<Attractor> {
         &self.attractors
     }
 
+    pub fn total_linear_momentum(&self) -> Vector2 {
+        self.bodies
+            .iter()
+            .fold(ZERO, |sum, b| sum + b.linear_momentum())
+    }
+
+    /// Angular momentum of all bodies around the origin, including their orbit around it.
+    pub fn total_angular_momentum(&self) -> f32 {
+        self.bodies
+            .iter()
+            .map(|b| b.angular_momentum() + b.center_of_mass().crossed(b.linear_momentum()))
+            .sum()
+    }
+
+    pub fn total_kinetic_energy(&self) -> f32 {
+        self.bodies.iter().map(Body::kinetic_energy).sum()
+    }
+
     pub fn get_delta_time(&self) -> f32 {
         self.delta_time
     }
@@ -130,7 +436,39 @@ impl World {
     }
 
     pub fn get_collision_precision(&self) -> usize {
-        self.collision_precision
+        self.solver_settings.get_precision()
+    }
+
+    pub fn is_sleeping_enabled(&self) -> bool {
+        self.sleeping_enabled
+    }
+
+    pub fn get_time_to_sleep(&self) -> f32 {
+        self.time_to_sleep
+    }
+
+    /// Returns the linear (m/s) and angular (rad/s) velocity below which bodies count as resting.
+    pub fn get_sleep_tolerances(&self) -> (f32, f32) {
+        (self.linear_sleep_tolerance, self.angular_sleep_tolerance)
+    }
+
+    /// Share of a step the accumulated time has reached.<br>
+    /// Used to interpolate between the previous and current transforms of bodies.
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        self.accumulator / self.delta_time
+    }
+
+    pub fn get_max_steps(&self) -> usize {
+        self.max_steps
+    }
+
+    pub fn get_solver_settings(&self) -> &SolverSettings {
+        &self.solver_settings
+    }
+
+    /// Solver settings validate all changes themselves.
+    pub fn get_solver_settings_mut(&mut self) -> &mut SolverSettings {
+        &mut self.solver_settings
     }
 
     // SETTERS
@@ -141,10 +479,97 @@ impl World {
 
     /// Defines how many iterations the collision pipeline should execute.<br>
     /// This is a tradeoff of accuracy and computiation complexity.<br><br>
-    /// The accuracy range is 10-100 and gets clamped upon setting it.<br>
+    /// The accuracy range is 10-100 by default and gets clamped upon setting it.<br>
     /// Higher accuracy means more expensive resolution.
     pub fn set_collision_precision(&mut self, precision: usize) {
-        self.collision_precision = precision.clamp(10, 100);
+        self.solver_settings.set_precision(precision);
+    }
+
+    /// Disabling sleeping wakes all bodies.
+    pub fn set_sleeping_enabled(&mut self, enabled: bool) {
+        self.sleeping_enabled = enabled;
+
+        if !enabled {
+            self.wake_all();
+        }
+    }
+
+    /// Defines how many seconds an island has to rest before it falls asleep.
+    pub fn set_time_to_sleep(&mut self, seconds: f32) {
+        self.time_to_sleep = f32::max(seconds, 0.);
+    }
+
+    /// Defines the linear (m/s) and angular (rad/s) velocity below which bodies count as resting.<br>
+    /// Negative tolerances get clamped to zero, which keeps every moving body awake.
+    pub fn set_sleep_tolerances(&mut self, linear: f32, angular: f32) {
+        self.linear_sleep_tolerance = f32::max(linear, 0.);
+        self.angular_sleep_tolerance = f32::max(angular, 0.);
+    }
+
+    /// Limits the steps a single call of advance may execute, at least one.<br>
+    /// If the simulation falls behind further, the remaining time gets dropped instead of piling up.
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = max_steps.max(1);
+    }
+
+    /// Replaces all solver settings, e.g. with one of the presets.
+    pub fn set_solver_settings(&mut self, settings: SolverSettings) {
+        self.solver_settings = settings;
+    }
+
+    /// Overrides friction and restitution of contacts between the materials with the given names.<br>
+    /// The order of the names does not matter.
+    pub fn set_material_pair(
+        &mut self,
+        name_a: &'static str,
+        name_b: &'static str,
+        pair: MaterialPair,
+    ) {
+        self.material_pairs
+            .insert(material_key(name_a, name_b), pair);
+    }
+
+    pub fn remove_material_pair(
+        &mut self,
+        name_a: &'static str,
+        name_b: &'static str,
+    ) -> Option<MaterialPair> {
+        self.material_pairs.remove(&material_key(name_a, name_b))
+    }
+
+    /// Installs a hook that can modify every contact before it gets resolved, replacing the previous one.
+    pub fn set_contact_modifier(&mut self, modifier: impl ContactModifier + 'static) {
+        self.contact_modifier = Some(Box::new(modifier));
+    }
+
+    pub fn remove_contact_modifier(&mut self) -> Option<Box<dyn ContactModifier>> {
+        self.contact_modifier.take()
+    }
+
+    /// Changes the collision filter of a body and discards all of its current contacts.<br>
+    /// The body and everything it touched gets woken up, so the pairs are reevaluated.
+    pub fn set_filter(&mut self, body_idx: usize, filter: CollisionFilter) {
+        if body_idx >= self.bodies.len() {
+            return;
+        }
+
+        let mut touched = vec![body_idx];
+
+        self.manifolds.retain(|&(a_idx, b_idx), _| {
+            if a_idx == body_idx || b_idx == body_idx {
+                touched.push(a_idx + b_idx - body_idx);
+                return false;
+            }
+            true
+        });
+        self.possible_collisions
+            .retain(|&(a_idx, b_idx)| a_idx != body_idx && b_idx != body_idx);
+
+        for idx in touched {
+            self.wake(idx);
+        }
+
+        self.bodies[body_idx].filter = filter;
     }
 }
 
@@ -180,67 +605,390 @@ impl World {
 
 // physics update (step)
 impl World {
+    /// Advances the world by the real time passed, in as many fixed steps as fit into it.<br>
+    /// Leftover time is kept for the next call, see get_interpolation_alpha.<br>
+    /// Returns the number of executed steps.
+    pub fn advance(&mut self, real_dt: f32) -> usize {
+        self.accumulator += f32::max(real_dt, 0.);
+        let mut steps = 0;
+
+        while self.accumulator >= self.delta_time {
+            if steps == self.max_steps {
+                self.accumulator %= self.delta_time;
+                break;
+            }
+
+            self.update();
+            self.accumulator -= self.delta_time;
+            steps += 1;
+        }
+
+        steps
+    }
+
     pub fn update(&mut self) {
         let update_start = Instant::now();
+        let previous_overlaps = mem::take(&mut self.sensor_overlaps);
+
+        for body in &mut self.bodies {
+            body.previous_transform = body.transform.clone();
+        }
 
         self.broad_phase();
         self.narrow_phase();
 
-        for body in self.bodies.iter_mut().filter(|b| b.body_type == Dynamic) {
+        // woken islands need their own contacts as well
+        while self.wake_touched_islands() {
+            self.broad_phase();
+            self.narrow_phase();
+        }
+
+        self.push_sensor_events(previous_overlaps);
+
+        let substepping = self.solver_settings.get_substepping();
+
+        if substepping.is_none() {
+            self.integrate_forces(self.delta_time);
+        }
+
+        self.apply_material_pairs();
+        self.filter_one_way_contacts();
+        self.modify_contacts();
+
+        match substepping {
+            Some(settings) => self.solve_substeps(settings),
+            None => self.solve(),
+        }
+
+        for body in &mut self.bodies {
+            body.clear_forces();
+        }
+
+        self.push_contact_events();
+        self.update_islands();
+        self.rebuild_grid();
+
+        self.last_step_duration = update_start.elapsed().as_secs_f32() * 1000.;
+    }
+}
+
+// queries
+impl World {
+    /// Returns the closest body hit by the ray, sensors and bodies the ray starts in are ignored.<br>
+    /// Only bodies whose filter collides with the given one can be hit.
+    pub fn raycast(
+        &self,
+        origin: Vector2,
+        direction: Vector2,
+        max_distance: f32,
+        filter: CollisionFilter,
+    ) -> Option<RaycastHit> {
+        self.ray_hits(origin, direction, max_distance, filter)
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Returns all bodies hit by the ray, sorted by distance.
+    pub fn raycast_all(
+        &self,
+        origin: Vector2,
+        direction: Vector2,
+        max_distance: f32,
+        filter: CollisionFilter,
+    ) -> Vec<RaycastHit> {
+        let mut hits: Vec<_> = self
+            .ray_hits(origin, direction, max_distance, filter)
+            .collect();
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Moves the shape from start along the translation and returns the first body it touches.<br>
+    /// Sensors are ignored, bodies the shape starts in are hit at a time of impact of 0.
+    pub fn shape_cast(
+        &self,
+        shape: &Shape,
+        start: Vector2,
+        rotation: f32,
+        translation: Vector2,
+        filter: CollisionFilter,
+    ) -> Option<ShapeCastHit> {
+        let mut probe = Body::probe(shape.clone(), start, rotation);
+        let candidates = self.candidates(&swept_hitbox(&probe, translation));
+
+        candidates
+            .into_iter()
+            .filter_map(|body_idx| {
+                let body = &self.bodies[body_idx];
+
+                if body.is_sensor || !filter.should_collide(&body.filter) {
+                    return None;
+                }
+
+                let (toi, point, normal) =
+                    shape_cast_body(&mut probe, translation, body, &self.solver_settings)?;
+
+                Some(ShapeCastHit {
+                    body_idx,
+                    toi,
+                    point,
+                    normal,
+                })
+            })
+            .min_by(|a, b| a.toi.total_cmp(&b.toi))
+    }
+
+    /// Returns all bodies enclosing the point, including sensors.
+    pub fn query_point(&self, point: Vector2) -> Vec<usize> {
+        self.candidates(&Hitbox::new(point, point))
+            .into_iter()
+            .filter(|&body_idx| self.bodies[body_idx].encloses(point))
+            .collect()
+    }
+
+    /// Returns all bodies whose shape overlaps the area, including sensors.
+    pub fn query_aabb(&self, area: Hitbox) -> Vec<usize> {
+        let size = area.max - area.min;
+        let center = (area.min + area.max) * 0.5;
+
+        self.query_shape(&Shape::AABB(AABB::new(size.x, size.y)), center, 0.)
+    }
+
+    /// Returns all bodies overlapping the shape, including sensors. Touching bodies do not overlap.
+    pub fn query_shape(&self, shape: &Shape, location: Vector2, rotation: f32) -> Vec<usize> {
+        let probe = Body::probe(shape.clone(), location, rotation);
+        let hitbox = &probe.hitbox + location;
+
+        self.candidates(&hitbox)
+            .into_iter()
+            .filter(|&body_idx| {
+                let body = &self.bodies[body_idx];
+                detect_collision(&probe, usize::MAX, body, body_idx, &self.solver_settings)
+                    .is_some()
+            })
+            .collect()
+    }
+
+    /// Returns the distance and closest points of both bodies, None if either does not exist.<br>
+    /// Overlapping bodies have a negative distance, the penetration depth.
+    pub fn distance(&self, a_idx: usize, b_idx: usize) -> Option<Separation> {
+        let (a, b) = (self.bodies.get(a_idx)?, self.bodies.get(b_idx)?);
+        Some(separation(a, b, &self.solver_settings))
+    }
+
+    /// Bodies whose hitbox overlaps the given one, all bodies if the grid is outdated.
+    fn candidates(&self, hitbox: &Hitbox) -> Vec<usize> {
+        if self.grid_outdated {
+            return (0..self.bodies.len())
+                .filter(|&body_idx| {
+                    let body = &self.bodies[body_idx];
+                    (&body.hitbox + body.transform.location).overlaps(hitbox)
+                })
+                .collect();
+        }
+
+        self.grid.query_hitbox(hitbox)
+    }
+
+    fn ray_hits(
+        &self,
+        origin: Vector2,
+        direction: Vector2,
+        max_distance: f32,
+        filter: CollisionFilter,
+    ) -> impl Iterator<Item = RaycastHit> + '_ {
+        let direction = direction.normalize().filter(|_| max_distance > 0.);
+
+        let candidates = match direction {
+            None => Vec::new(),
+            Some(_) if self.grid_outdated => (0..self.bodies.len()).collect(),
+            Some(direction) => self.grid.query_ray(origin, direction, max_distance),
+        };
+
+        candidates.into_iter().filter_map(move |body_idx| {
+            let body = &self.bodies[body_idx];
+
+            if body.is_sensor || !filter.should_collide(&body.filter) {
+                return None;
+            }
+
+            let (distance, normal) = raycast_body(body, origin, direction?, max_distance)?;
+
+            Some(RaycastHit {
+                body_idx,
+                point: origin + direction? * distance,
+                normal,
+                distance,
+                fraction: distance / max_distance,
+            })
+        })
+    }
+}
+
+// solvers
+impl World {
+    fn integrate_forces(&mut self, dt: f32) {
+        for body in self.bodies.iter_mut().filter(|b| b.is_active()) {
             let mut f = self.forces;
 
             self.attractors.iter().for_each(|a| {
                 f += a.get_attraction(body);
             });
 
-            body.transform.velocity += f * self.delta_time;
+            body.transform.velocity += f * body.gravity_scale * dt;
+            body.integrate_force(dt);
+            body.damp_velocity(dt);
         }
+    }
 
-        if !self.manifolds.is_empty() {
-            self.setup_resolutions();
+    fn integrate_velocities(&mut self, dt: f32) {
+        for body in self.bodies.iter_mut().filter(|b| b.is_active()) {
+            body.integrate_velocity(dt);
+        }
+    }
 
-            for _ in 0..self.collision_precision {
-                self.resolve_collisions();
-            }
+    fn solve(&mut self) {
+        self.setup_resolutions();
+        self.setup_joints(1. / self.delta_time);
+
+        for _ in 0..self.solver_settings.get_precision() {
+            self.solve_joints();
+            self.resolve_collisions();
+        }
+
+        self.correct_positions();
+
+        self.integrate_velocities(self.delta_time);
+    }
+
+    /// Splits the step into substeps with soft contacts, contacts are only detected once.
+    fn solve_substeps(&mut self, settings: SubstepSettings) {
+        let substeps = settings.substeps.max(1);
+        let h = self.delta_time / substeps as f32;
+        let inv_h = 1. / h;
 
-            self.correct_positions();
-            self.manifolds.clear();
+        let frequency = f32::min(settings.contact_frequency, 0.25 * inv_h);
+        let softness = Softness::new(frequency, settings.contact_damping, h);
+
+        let origins: Vec<Origin> = self
+            .bodies
+            .iter()
+            .map(|b| (b.transform.location, b.transform.rotation))
+            .collect();
+
+        for (pair, m) in self.manifolds.iter_mut() {
+            let (a, b) = (&self.bodies[m.a_idx], &self.bodies[m.b_idx]);
+            let previous = self.cached_contacts.get(pair);
+            prepare_soft_contact(m, a, b, &self.solver_settings, previous);
         }
 
-        for body in self.bodies.iter_mut().filter(|b| b.body_type == Dynamic) {
-            body.transform.location += body.transform.velocity * self.delta_time;
-            body.rotate(self.delta_time);
+        for _ in 0..substeps {
+            self.integrate_forces(h);
+            self.setup_joints(inv_h);
+
+            for m in self.manifolds.values_mut() {
+                warm_start_soft_contact(m, &mut self.bodies);
+            }
+
+            self.solve_joints();
+            for m in self.manifolds.values_mut() {
+                let max_push = settings.max_push_velocity;
+                solve_soft_contact(
+                    m,
+                    &mut self.bodies,
+                    &origins,
+                    Some(softness),
+                    max_push,
+                    inv_h,
+                );
+            }
+
+            for _ in 0..self.solver_settings.get_joint_position_iterations() {
+                self.correct_joint_positions();
+            }
+
+            self.integrate_velocities(h);
+
+            for _ in 0..settings.relax_iterations {
+                self.solve_joints();
+                for m in self.manifolds.values_mut() {
+                    solve_soft_contact(m, &mut self.bodies, &origins, None, 0., inv_h);
+                }
+            }
         }
 
-        self.last_step_duration = update_start.elapsed().as_secs_f32() * 1000.;
+        // cached before restitution, the bounce should not carry over into the next step
+        self.cached_contacts = self
+            .manifolds
+            .iter()
+            .map(|(&pair, m)| (pair, CachedContacts::new(m)))
+            .collect();
+
+        for m in self.manifolds.values_mut() {
+            apply_restitution(m, &mut self.bodies);
+        }
     }
 }
 
 // collisions
 impl World {
-    // More efficient way of doing this would be a QuadTree algortithm
+    /// Only compares bodies that share a cell of the grid.
     fn broad_phase(&mut self) {
         self.collision_points.clear();
+        self.manifolds.clear();
+        self.sensor_overlaps.clear();
+
+        if self.grid_outdated {
+            self.rebuild_grid();
+        }
 
         let body_count = self.bodies.len();
 
+        let connected: HashSet<(usize, usize)> = self
+            .joints
+            .iter()
+            .filter_map(Joint::connected_pair)
+            .collect();
+
         for a_idx in 0..body_count {
             let a = &self.bodies[a_idx];
 
-            for b_idx in (a_idx + 1)..body_count {
+            let hitbox = self.grid.hitbox(a_idx);
+            let candidates = self.grid.query_cells(hitbox);
+
+            for b_idx in candidates.into_iter().filter(|&b_idx| b_idx > a_idx) {
                 let b = &self.bodies[b_idx];
 
-                if a.body_type == Static && b.body_type == Static {
+                if a.is_sensor || b.is_sensor {
+                    // static sensors still have to notice sleeping bodies inside them
+                    if a.is_sensor == b.is_sensor
+                        || (a.body_type == Static && b.body_type == Static)
+                    {
+                        continue;
+                    }
+                } else if !a.is_active() && !b.is_active() {
+                    // static and sleeping bodies do not move, so they cannot collide with each other
                     continue;
                 }
 
-                if hitboxes_collide(a, b) {
-                    self.possible_collisions.push((a_idx, b_idx));
+                if connected.contains(&(a_idx, b_idx)) || !a.can_collide_with(b) {
+                    continue;
                 }
+
+                if !self.grid.hitbox(b_idx).overlaps(hitbox) {
+                    continue;
+                }
+
+                self.possible_collisions.push((a_idx, b_idx));
             }
         }
     }
 
+    fn rebuild_grid(&mut self) {
+        self.grid.rebuild(&self.bodies);
+        self.grid_outdated = false;
+    }
+
     fn narrow_phase(&mut self) {
         let pc = mem::take(&mut self.possible_collisions);
 
@@ -248,7 +996,16 @@ impl World {
             let (a_idx, b_idx) = coll;
             let (a, b) = (&self.bodies[a_idx], &self.bodies[b_idx]);
 
-            if let Some(manifold) = detect_collision(a, a_idx, b, b_idx) {
+            if let Some(manifold) = detect_collision(a, a_idx, b, b_idx, &self.solver_settings) {
+                // sensors only report overlaps and never get resolved
+                if a.is_sensor {
+                    self.sensor_overlaps.insert((a_idx, b_idx));
+                    continue;
+                } else if b.is_sensor {
+                    self.sensor_overlaps.insert((b_idx, a_idx));
+                    continue;
+                }
+
                 for idx in 0..manifold.contact_count {
                     self.collision_points.push(manifold.contacts[idx].location);
                 }
@@ -257,11 +1014,200 @@ impl World {
         }
     }
 
+    /// Compares the overlaps of this step with the previous one, ended overlaps are reported first.
+    fn push_sensor_events(&mut self, previous_overlaps: HashSet<(usize, usize)>) {
+        let mut ended: Vec<_> = previous_overlaps
+            .difference(&self.sensor_overlaps)
+            .copied()
+            .collect();
+        let mut began: Vec<_> = self
+            .sensor_overlaps
+            .difference(&previous_overlaps)
+            .copied()
+            .collect();
+
+        ended.sort_unstable();
+        began.sort_unstable();
+
+        self.sensor_events.extend(
+            ended
+                .into_iter()
+                .map(|(sensor, visitor)| SensorEvent::EndOverlap { sensor, visitor }),
+        );
+        self.sensor_events.extend(
+            began
+                .into_iter()
+                .map(|(sensor, visitor)| SensorEvent::BeginOverlap { sensor, visitor }),
+        );
+    }
+
+    fn push_contact_events(&mut self) {
+        let mut pairs: Vec<_> = self.manifolds.keys().copied().collect();
+        pairs.sort_unstable();
+
+        for pair in pairs {
+            let m = &self.manifolds[&pair];
+            let (normal_impulse, tangent_impulse) = m.total_impulses();
+
+            let phase = if self.touching.insert(pair) {
+                ContactPhase::Begin
+            } else {
+                ContactPhase::Persist
+            };
+
+            // some detections swap the bodies, events always use the ordered pair
+            let normal = if m.a_idx == pair.0 {
+                m.normal
+            } else {
+                ZERO - m.normal
+            };
+
+            self.contact_events.push(ContactEvent {
+                phase,
+                a_idx: pair.0,
+                b_idx: pair.1,
+                normal,
+                points: m.contacts[..m.contact_count]
+                    .iter()
+                    .map(|c| c.location)
+                    .collect(),
+                normal_impulse,
+                tangent_impulse,
+            });
+        }
+
+        let bodies = &self.bodies;
+        let manifolds = &self.manifolds;
+        let mut ended = Vec::new();
+
+        // resting contacts of sleeping islands are kept, since the bodies still touch
+        self.touching.retain(|pair| {
+            let resting = !bodies[pair.0].is_active() && !bodies[pair.1].is_active();
+
+            if manifolds.contains_key(pair) || resting {
+                return true;
+            }
+
+            ended.push(*pair);
+            false
+        });
+
+        ended.sort_unstable();
+
+        self.contact_events.extend(
+            ended
+                .into_iter()
+                .map(|(a_idx, b_idx)| ContactEvent::ended(a_idx, b_idx)),
+        );
+    }
+
+    /// Ends all contacts and sensor overlaps of a body that is about to be removed.
+    fn end_pairs_of(&mut self, body_idx: usize) {
+        let involved = |&&(a_idx, b_idx): &&(usize, usize)| a_idx == body_idx || b_idx == body_idx;
+
+        let mut contacts: Vec<_> = self.touching.iter().filter(involved).copied().collect();
+        let mut overlaps: Vec<_> = self
+            .sensor_overlaps
+            .iter()
+            .filter(involved)
+            .copied()
+            .collect();
+
+        contacts.sort_unstable();
+        overlaps.sort_unstable();
+
+        self.contact_events.extend(
+            contacts
+                .into_iter()
+                .map(|(a_idx, b_idx)| ContactEvent::ended(a_idx, b_idx)),
+        );
+        self.sensor_events.extend(
+            overlaps
+                .into_iter()
+                .map(|(sensor, visitor)| SensorEvent::EndOverlap { sensor, visitor }),
+        );
+    }
+
+    fn apply_material_pairs(&mut self) {
+        if self.material_pairs.is_empty() {
+            return;
+        }
+
+        for m in self.manifolds.values_mut() {
+            let (a, b) = (&self.bodies[m.a_idx], &self.bodies[m.b_idx]);
+            let key = material_key(a.material.name, b.material.name);
+
+            if let Some(pair) = self.material_pairs.get(&key) {
+                m.friction = pair.friction;
+                m.restitution = pair.restitution;
+
+                if let Some(rolling_resistance) = pair.rolling_resistance {
+                    m.rolling_resistance = rolling_resistance * rolling_radius(a, b);
+                }
+            }
+        }
+    }
+
+    /// Disables new contacts with one-way bodies that push against the allowed direction,
+    /// or where the other body already was inside at the start of the step.<br>
+    /// Disabled pairs pass through each other until they separate.
+    fn filter_one_way_contacts(&mut self) {
+        let bodies = &self.bodies;
+        let dt = self.delta_time;
+        let one_way_slop = self.solver_settings.get_one_way_slop();
+
+        self.one_way_passing
+            .retain(|pair| self.manifolds.contains_key(pair));
+
+        for (&pair, m) in &mut self.manifolds {
+            for (platform_idx, sign) in [(m.a_idx, 1.), (m.b_idx, -1.)] {
+                let Some(direction) = bodies[platform_idx].one_way else {
+                    continue;
+                };
+
+                if self.one_way_passing.contains(&pair) {
+                    m.enabled = false;
+                    break;
+                }
+
+                // only the start of a contact decides, bodies resting on top stay there
+                if self.touching.contains(&pair) {
+                    break;
+                }
+
+                let (a, b) = (&bodies[m.a_idx], &bodies[m.b_idx]);
+
+                // the normal points from a to b, so flip it if b is the platform
+                let normal = m.normal * sign;
+                let approach = (a.transform.velocity - b.transform.velocity).dotted(m.normal);
+
+                let against = normal.dotted(direction.normalize_or_zero()) <= 0.;
+                let started_inside = m.depth > f32::max(approach, 0.) * dt + one_way_slop;
+
+                if against || started_inside {
+                    self.one_way_passing.insert(pair);
+                    m.enabled = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn modify_contacts(&mut self) {
+        if let Some(modifier) = self.contact_modifier.as_mut() {
+            for m in self.manifolds.values_mut().filter(|m| m.enabled) {
+                modifier.pre_solve(m, &self.bodies[m.a_idx], &self.bodies[m.b_idx]);
+            }
+        }
+
+        self.manifolds.retain(|_, m| m.enabled);
+    }
+
     fn setup_resolutions(&mut self) {
         for m in self.manifolds.values_mut() {
             let a = &self.bodies[m.a_idx];
             let b = &self.bodies[m.b_idx];
-            m.setup(a, b, self.forces * self.delta_time);
+            m.setup(a, b, self.solver_settings.get_restitution_threshold());
         }
     }
 
@@ -271,9 +1217,211 @@ impl World {
         }
     }
 
+    fn setup_joints(&mut self, inv_dt: f32) {
+        let correction_factor = self.solver_settings.get_joint_correction_factor();
+
+        // joints between static or sleeping bodies do not need to be solved
+        self.active_joints = (0..self.joints.len())
+            .filter(|&idx| {
+                self.joints[idx]
+                    .body_indices()
+                    .into_iter()
+                    .any(|body_idx| self.bodies[body_idx].is_active())
+            })
+            .collect();
+
+        for &idx in &self.active_joints {
+            self.joints[idx].setup(&self.bodies, inv_dt, correction_factor);
+        }
+
+        // gears need access to the joints they couple
+        for &idx in &self.active_joints {
+            if let Joint::Gear(gear) = &self.joints[idx] {
+                let mut gear = gear.clone();
+                gear.setup(&self.joints, &self.bodies, inv_dt, correction_factor);
+                self.joints[idx] = Joint::Gear(gear);
+            }
+        }
+
+        for &idx in &self.active_joints {
+            self.joints[idx].warm_start(&mut self.bodies);
+        }
+    }
+
+    fn solve_joints(&mut self) {
+        for &idx in &self.active_joints {
+            self.joints[idx].solve(&mut self.bodies);
+        }
+    }
+
+    /// Pushes overlapping and jointed bodies apart via pseudo velocities.<br>
+    /// Joints and contacts take turns, so neither of them undoes the correction of the other.
     fn correct_positions(&mut self) {
-        for m in self.manifolds.values_mut() {
-            correct_position(m, &mut self.bodies);
+        let inv_dt = 1. / self.delta_time;
+        let precision = self.solver_settings.get_precision();
+        let joint_iterations = self.solver_settings.get_joint_position_iterations();
+
+        for iteration in 0..usize::max(precision, joint_iterations) {
+            if iteration < precision {
+                for m in self.manifolds.values_mut() {
+                    correct_position(m, &mut self.bodies, &self.solver_settings, inv_dt);
+                }
+            }
+
+            if iteration < joint_iterations {
+                self.correct_joint_positions();
+            }
+        }
+    }
+
+    fn correct_joint_positions(&mut self) {
+        for &idx in &self.active_joints {
+            self.joints[idx].correct_position(&mut self.bodies);
+        }
+    }
+}
+
+// sleeping
+impl World {
+    /// Wakes the body and every other body in its island.
+    pub fn wake(&mut self, body_idx: usize) {
+        let Some(body) = self.bodies.get_mut(body_idx) else {
+            return;
+        };
+
+        if !body.is_sleeping() {
+            body.wake();
+            return;
+        }
+
+        let island = body.island;
+
+        for body in self.bodies.iter_mut().filter(|b| b.is_sleeping()) {
+            if body.island == island {
+                body.wake();
+            }
+        }
+    }
+
+    /// Wakes the body, every body overlapping it or connected to it by a joint, and their islands.<br>
+    /// Static bodies do not belong to an island, so waking them alone leaves their neighbours asleep.
+    fn wake_touching(&mut self, body_idx: usize) {
+        let body = &self.bodies[body_idx];
+
+        let mut touched: Vec<usize> = (0..self.bodies.len())
+            .filter(|&idx| idx != body_idx && hitboxes_collide(body, &self.bodies[idx]))
+            .collect();
+
+        for joint in &self.joints {
+            let indices = joint.body_indices();
+
+            if indices.contains(&body_idx) {
+                touched.extend(indices);
+            }
+        }
+
+        self.wake(body_idx);
+
+        for idx in touched {
+            self.wake(idx);
+        }
+    }
+
+    pub fn wake_all(&mut self) {
+        for body in &mut self.bodies {
+            body.wake();
+        }
+    }
+
+    /// Wakes all sleeping islands that are touched by active bodies, either via contacts or joints.<br>
+    /// Returns true if any island was woken up.
+    fn wake_touched_islands(&mut self) -> bool {
+        let mut touched = Vec::new();
+
+        for &(a_idx, b_idx) in self.manifolds.keys() {
+            let (a, b) = (&self.bodies[a_idx], &self.bodies[b_idx]);
+
+            if a.is_active() && b.is_sleeping() {
+                touched.push(b_idx);
+            } else if b.is_active() && a.is_sleeping() {
+                touched.push(a_idx);
+            }
+        }
+
+        for joint in &self.joints {
+            let indices = joint.body_indices();
+
+            if indices.iter().any(|&idx| self.bodies[idx].is_active()) {
+                touched.extend(
+                    indices
+                        .into_iter()
+                        .filter(|&idx| self.bodies[idx].is_sleeping()),
+                );
+            }
+        }
+
+        for &body_idx in &touched {
+            self.wake(body_idx);
+        }
+
+        !touched.is_empty()
+    }
+
+    /// Builds islands out of all active bodies and puts every island to sleep,
+    /// where all bodies have been resting for long enough.
+    fn update_islands(&mut self) {
+        if !self.sleeping_enabled {
+            return;
+        }
+
+        let mut islands = Islands::new(self.bodies.len());
+
+        for &(a_idx, b_idx) in self.manifolds.keys() {
+            if self.bodies[a_idx].is_active() && self.bodies[b_idx].is_active() {
+                islands.union(a_idx, b_idx);
+            }
+        }
+
+        for joint in &self.joints {
+            let active: Vec<usize> = joint
+                .body_indices()
+                .into_iter()
+                .filter(|&idx| self.bodies[idx].is_active())
+                .collect();
+
+            for pair in active.windows(2) {
+                islands.union(pair[0], pair[1]);
+            }
+        }
+
+        // an island can only be as sleepy as its most restless body
+        let mut island_sleep_times: HashMap<usize, f32> = HashMap::new();
+
+        for (idx, body) in self.bodies.iter_mut().enumerate() {
+            if !body.is_active() {
+                continue;
+            }
+
+            let sleep_time = body.update_sleep_time(
+                self.delta_time,
+                self.linear_sleep_tolerance,
+                self.angular_sleep_tolerance,
+            );
+            let root = islands.find(idx);
+
+            // the root is a body of this island, so the id never clashes with a sleeping island
+            body.island = root;
+
+            island_sleep_times
+                .entry(root)
+                .and_modify(|t| *t = f32::min(*t, sleep_time))
+                .or_insert(sleep_time);
+        }
+
+        for (idx, body) in self.bodies.iter_mut().enumerate() {
+            if body.is_active() && island_sleep_times[&islands.find(idx)] >= self.time_to_sleep {
+                body.sleep();
+            }
         }
     }
 }
@@ -287,3 +1435,198 @@ impl Display for World {
         )
     }
 }
+
+/// Index of a body after the body with removed_idx was removed.
+fn shifted(idx: usize, removed_idx: usize) -> usize {
+    if idx > removed_idx {
+        idx - 1
+    } else {
+        idx
+    }
+}
+
+fn material_key(name_a: &'static str, name_b: &'static str) -> (&'static str, &'static str) {
+    if name_a <= name_b {
+        (name_a, name_b)
+    } else {
+        (name_b, name_a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::material::DEFAULT,
+        shapes::{circle::Circle, polygon::Polygon},
+        test_utils::{run, sensor, world},
+    };
+    use std::f32::consts::PI;
+
+    #[test]
+    fn one_way_platforms_can_be_passed_from_below() {
+        let mut world = world();
+        let mut platform = Body::platform_rectangle_aabb(0., 2., 4., 0.2, DEFAULT);
+        platform.one_way = Some(Vector2::new(0., 1.));
+        world.add_body(platform);
+
+        let ball = world.add_body(Body::circle(0., 0., 0.3, DEFAULT));
+        world.get_body_mut(ball).unwrap().transform.velocity = Vector2::new(0., 10.);
+
+        run(&mut world, 300);
+
+        let body = &world.get_bodies()[ball];
+        assert!(
+            (body.transform.location.y - 2.4).abs() < 0.05,
+            "{}",
+            body.transform.location
+        );
+    }
+
+    #[test]
+    fn one_way_platforms_block_from_above() {
+        let mut world = world();
+        let mut platform = Body::platform_rectangle_aabb(0., 2., 4., 0.2, DEFAULT);
+        platform.one_way = Some(Vector2::new(0., 1.));
+        world.add_body(platform);
+
+        let ball = world.add_body(Body::circle(0., 4., 0.3, DEFAULT));
+
+        run(&mut world, 120);
+
+        assert!(world.get_bodies()[ball].transform.location.y > 2.3);
+    }
+
+    #[test]
+    fn rotated_one_way_polygons_can_be_passed_from_below_only() {
+        let rotation = 0.2;
+        let up = Vector2::rotated(Vector2::new(0., 1.), rotation);
+        let along = Vector2::rotated(Vector2::new(1., 0.), rotation);
+        let center = Vector2::new(0., 2.);
+
+        let vertices = vec![
+            Vector2::new(-3., -0.1),
+            Vector2::new(3., -0.1),
+            Vector2::new(3., 0.1),
+            Vector2::new(-3., 0.1),
+        ];
+
+        let mut world = world();
+        let mut platform =
+            Body::platform_polygon(center.x, center.y, vertices, rotation, DEFAULT).unwrap();
+        platform.one_way = Some(up);
+        world.add_body(platform);
+
+        let ball = world.add_body(Body::circle(0., 0., 0.3, DEFAULT));
+        world.get_body_mut(ball).unwrap().transform.velocity = Vector2::new(0., 10.);
+
+        let mut passed = false;
+
+        for _ in 0..240 {
+            world.update();
+
+            let offset = world.get_bodies()[ball].transform.location - center;
+            let height = offset.dotted(up);
+
+            // once on top, the ball has to stay there while it is above the platform
+            if passed && offset.dotted(along).abs() < 2.5 {
+                assert!(height > 0.3, "fell through at height {height}");
+            }
+
+            passed |= height > 0.5;
+        }
+
+        assert!(passed);
+    }
+
+    #[test]
+    fn queries_find_bodies_before_and_after_a_step() {
+        let mut world = World::new(60., 10.);
+        let ground = world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+        let ball = world.add_body(Body::platform_circle(3., 2., 0.5, DEFAULT));
+        let trigger = sensor(&mut world, -3., 2., 1.);
+
+        // the first round uses the outdated grid, the second the rebuilt one
+        for _ in 0..2 {
+            let mut under_cursor = world.query_point(Vector2::new(3.2, 2.));
+            under_cursor.sort();
+            assert_eq!(under_cursor, vec![ball]);
+            assert_eq!(world.query_point(Vector2::new(-3., 2.)), vec![trigger]);
+            assert!(world.query_point(Vector2::new(0., 5.)).is_empty());
+
+            let mut area =
+                world.query_aabb(Hitbox::new(Vector2::new(-4., 1.), Vector2::new(4., 3.)));
+            area.sort();
+            assert_eq!(area, vec![ball, trigger]);
+
+            let probe = Shape::Circle(Circle::new(1.));
+            let mut overlapping = world.query_shape(&probe, Vector2::new(3., 0.7), 0.);
+            overlapping.sort();
+            assert_eq!(overlapping, vec![ground, ball]);
+
+            // touching the ball is not enough
+            let touching = world.query_shape(&probe, Vector2::new(3., 0.5), 0.);
+            assert_eq!(touching, vec![ground]);
+
+            world.update();
+        }
+    }
+
+    #[test]
+    fn rotated_query_shapes_only_find_what_they_cover() {
+        let mut world = World::new(60., 10.);
+        let corner = world.add_body(Body::platform_rectangle_aabb(0.9, 0.9, 0.2, 0.2, DEFAULT));
+
+        let corners = AABB::generate_corners(2., 2.).to_vec();
+        let square = Shape::Polygon(Polygon::new(corners).unwrap());
+
+        assert_eq!(world.query_shape(&square, ZERO, 0.), vec![corner]);
+        assert!(world.query_shape(&square, ZERO, PI / 4.).is_empty());
+    }
+
+    #[test]
+    fn advance_runs_fixed_steps_for_the_real_time() {
+        let mut world = World::new(60., 10.);
+
+        assert_eq!(world.advance(0.01), 0);
+        assert!((world.get_interpolation_alpha() - 0.6).abs() < 0.001);
+
+        assert_eq!(world.advance(0.01), 1);
+        assert!((world.get_interpolation_alpha() - 0.2).abs() < 0.001);
+
+        // negative time is ignored
+        assert_eq!(world.advance(-1.), 0);
+        assert!((world.get_interpolation_alpha() - 0.2).abs() < 0.001);
+    }
+
+    #[test]
+    fn advance_caps_the_steps_of_long_frames() {
+        let mut world = World::new(60., 10.);
+        world.set_max_steps(3);
+
+        assert_eq!(world.advance(1.), 3);
+        assert!(world.get_interpolation_alpha() < 1.);
+
+        // the dropped time does not pile up for the next frame
+        assert_eq!(world.advance(1. / 60.), 1);
+    }
+
+    #[test]
+    fn interpolation_blends_the_last_two_steps() {
+        let mut world = world();
+        let body = world.add_body(Body::circle(0., 10., 0.5, DEFAULT));
+
+        world.advance(2.5 / 60.);
+        let alpha = world.get_interpolation_alpha();
+        assert!((alpha - 0.5).abs() < 0.001);
+
+        let body = &world.get_bodies()[body];
+        let (previous, current) = (body.get_previous_transform(), &body.transform);
+        assert!(current.location.y < previous.location.y);
+
+        let halfway = body.interpolated_transform(alpha);
+        let expected = (previous.location.y + current.location.y) * 0.5;
+        assert!((halfway.location.y - expected).abs() < 0.0001);
+        assert_eq!(body.interpolated_transform(1.).location, current.location);
+    }
+}
@@ -0,0 +1,195 @@
+//! Distance joint, keeps two anchor points at a fixed distance or within a range of distances.<br>
+//! With a minimum of zero it behaves like a rope: it can go slack, but never stretch beyond its maximum.
+
+use crate::{
+    entities::body::Body,
+    joints::{
+        correction_bias, inverse_or_zero, limit_bias, limit_correction_bias, to_local, to_world,
+        Jacobian,
+    },
+    math::{vector2::ZERO, Vector2},
+};
+
+/// Anchors closer than this do not have a defined direction anymore.
+const MIN_LENGTH: f32 = 0.005;
+
+#[derive(Clone, Debug, Default)]
+pub struct DistanceJoint {
+    pub a_idx: usize,
+    pub b_idx: usize,
+    pub local_anchor_a: Vector2,
+    pub local_anchor_b: Vector2,
+    pub min_length: f32,
+    pub max_length: f32,
+    pub collide_connected: bool,
+
+    // recalculated by setup every step
+    jacobian: Jacobian,
+    mass: f32,
+    correction: f32,
+    lower_bias: f32,
+    upper_bias: f32,
+    lower_correction: f32,
+    upper_correction: f32,
+
+    // accumulated impulses, kept across steps for warm starting
+    impulse: f32,
+    lower_impulse: f32,
+    upper_impulse: f32,
+
+    // accumulated pseudo impulses of the position correction, reset every step
+    lower_pseudo_impulse: f32,
+    upper_pseudo_impulse: f32,
+}
+
+impl DistanceJoint {
+    /// Creates a rigid distance joint between two anchors given in world coordinates.<br>
+    /// The distance the anchors have on creation is kept.
+    pub fn new(
+        a: &Body,
+        a_idx: usize,
+        b: &Body,
+        b_idx: usize,
+        anchor_a: Vector2,
+        anchor_b: Vector2,
+    ) -> DistanceJoint {
+        let length = (anchor_b - anchor_a).len();
+
+        DistanceJoint {
+            a_idx,
+            b_idx,
+            local_anchor_a: to_local(a, anchor_a - a.transform.location),
+            local_anchor_b: to_local(b, anchor_b - b.transform.location),
+            min_length: length,
+            max_length: length,
+            ..Default::default()
+        }
+    }
+
+    /// Lets the distance vary freely between the given lengths.
+    pub fn range(mut self, min_length: f32, max_length: f32) -> Self {
+        self.min_length = f32::max(f32::min(min_length, max_length), 0.);
+        self.max_length = f32::max(f32::max(min_length, max_length), 0.);
+        self
+    }
+
+    pub fn collide_connected(mut self, collide_connected: bool) -> Self {
+        self.collide_connected = collide_connected;
+        self
+    }
+}
+
+impl DistanceJoint {
+    pub fn get_length(&self, bodies: &[Body]) -> f32 {
+        let a = &bodies[self.a_idx];
+        let b = &bodies[self.b_idx];
+
+        let anchor_a = a.transform.location + to_world(a, self.local_anchor_a);
+        let anchor_b = b.transform.location + to_world(b, self.local_anchor_b);
+
+        (anchor_b - anchor_a).len()
+    }
+
+    fn is_rigid(&self) -> bool {
+        self.max_length - self.min_length < MIN_LENGTH
+    }
+
+    pub(crate) fn setup(&mut self, bodies: &[Body], inv_dt: f32, correction_factor: f32) {
+        let a = &bodies[self.a_idx];
+        let b = &bodies[self.b_idx];
+
+        let ra = to_world(a, self.local_anchor_a);
+        let rb = to_world(b, self.local_anchor_b);
+        let d = (b.transform.location + rb) - (a.transform.location + ra);
+        let length = d.len();
+
+        let u = if length > MIN_LENGTH {
+            d * (1. / length)
+        } else {
+            ZERO
+        };
+
+        self.jacobian = Jacobian {
+            a_idx: self.a_idx,
+            b_idx: self.b_idx,
+            linear_a: ZERO - u,
+            angular_a: -ra.crossed(u),
+            linear_b: u,
+            angular_b: rb.crossed(u),
+        };
+        self.mass = inverse_or_zero(self.jacobian.inverse_mass(bodies));
+
+        self.correction = correction_bias(length - self.max_length, correction_factor, inv_dt);
+        self.lower_bias = limit_bias(length - self.min_length, inv_dt);
+        self.upper_bias = limit_bias(self.max_length - length, inv_dt);
+        self.lower_correction =
+            limit_correction_bias(length - self.min_length, correction_factor, inv_dt);
+        self.upper_correction =
+            limit_correction_bias(self.max_length - length, correction_factor, inv_dt);
+
+        self.lower_pseudo_impulse = 0.;
+        self.upper_pseudo_impulse = 0.;
+    }
+
+    pub(crate) fn warm_start(&self, bodies: &mut [Body]) {
+        let impulse = self.impulse + self.lower_impulse - self.upper_impulse;
+        self.jacobian.apply(bodies, impulse);
+    }
+
+    pub(crate) fn solve(&mut self, bodies: &mut [Body]) {
+        if self.mass == 0. {
+            return;
+        }
+
+        if self.is_rigid() {
+            let impulse = -self.mass * self.jacobian.velocity(bodies);
+            self.jacobian.apply(bodies, impulse);
+            self.impulse += impulse;
+            return;
+        }
+
+        // lower limit, length - min >= 0
+        if self.min_length > 0. {
+            self.jacobian
+                .solve_limit(bodies, self.mass, self.lower_bias, &mut self.lower_impulse);
+        }
+
+        // upper limit, max - length >= 0
+        self.jacobian.scaled(-1.).solve_limit(
+            bodies,
+            self.mass,
+            self.upper_bias,
+            &mut self.upper_impulse,
+        );
+    }
+
+    /// Pushes the anchors back to the rigid length or into the range of lengths.
+    pub(crate) fn correct_position(&mut self, bodies: &mut [Body]) {
+        if self.mass == 0. {
+            return;
+        }
+
+        if self.is_rigid() {
+            let impulse =
+                -self.mass * (self.jacobian.integrated_velocity(bodies) + self.correction);
+            self.jacobian.apply_pseudo(bodies, impulse);
+            return;
+        }
+
+        if self.min_length > 0. {
+            self.jacobian.correct_limit(
+                bodies,
+                self.mass,
+                self.lower_correction,
+                &mut self.lower_pseudo_impulse,
+            );
+        }
+
+        self.jacobian.scaled(-1.).correct_limit(
+            bodies,
+            self.mass,
+            self.upper_correction,
+            &mut self.upper_pseudo_impulse,
+        );
+    }
+}
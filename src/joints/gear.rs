@@ -0,0 +1,120 @@
+//! Gear joint, couples the coordinates of two revolute or prismatic joints.<br>
+//! coordinate_1 + ratio * coordinate_2 stays constant, where the coordinate is the
+//! angle of a revolute joint or the translation of a prismatic joint.
+
+use crate::{
+    entities::body::Body,
+    joints::{correction_bias, inverse_or_zero, Jacobian, Joint},
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct GearJoint {
+    pub joint_1: usize,
+    pub joint_2: usize,
+    pub ratio: f32,
+    pub constant: f32,
+    /// Bodies of both coupled joints: [a_1, b_1, a_2, b_2]
+    pub bodies: [usize; 4],
+
+    // recalculated by setup every step
+    jacobian_1: Jacobian,
+    jacobian_2: Jacobian,
+    mass: f32,
+    correction: f32,
+
+    // accumulated impulses, kept across steps for warm starting
+    impulse: f32,
+}
+
+impl GearJoint {
+    /// Creates a gear joint between two joints living in the world.<br>
+    /// Returns None if either joint does not exist or is neither revolute nor prismatic.
+    pub fn new(
+        joints: &[Joint],
+        bodies: &[Body],
+        joint_1: usize,
+        joint_2: usize,
+        ratio: f32,
+    ) -> Option<GearJoint> {
+        let (coordinate_1, jacobian_1) = joints.get(joint_1)?.coordinate(bodies)?;
+        let (coordinate_2, jacobian_2) = joints.get(joint_2)?.coordinate(bodies)?;
+
+        Some(GearJoint {
+            joint_1,
+            joint_2,
+            ratio,
+            constant: coordinate_1 + ratio * coordinate_2,
+            bodies: [
+                jacobian_1.a_idx,
+                jacobian_1.b_idx,
+                jacobian_2.a_idx,
+                jacobian_2.b_idx,
+            ],
+            ..Default::default()
+        })
+    }
+}
+
+impl GearJoint {
+    pub(crate) fn setup(
+        &mut self,
+        joints: &[Joint],
+        bodies: &[Body],
+        inv_dt: f32,
+        correction_factor: f32,
+    ) {
+        let coupled = (
+            joints[self.joint_1].coordinate(bodies),
+            joints[self.joint_2].coordinate(bodies),
+        );
+
+        let (Some((coordinate_1, jacobian_1)), Some((coordinate_2, jacobian_2))) = coupled else {
+            self.mass = 0.;
+            return;
+        };
+
+        self.jacobian_1 = jacobian_1;
+        self.jacobian_2 = jacobian_2.scaled(self.ratio);
+        self.mass = inverse_or_zero(
+            self.jacobian_1.inverse_mass(bodies) + self.jacobian_2.inverse_mass(bodies),
+        );
+
+        let error = coordinate_1 + self.ratio * coordinate_2 - self.constant;
+        self.correction = correction_bias(error, correction_factor, inv_dt);
+    }
+
+    pub(crate) fn warm_start(&self, bodies: &mut [Body]) {
+        if self.mass == 0. {
+            return;
+        }
+
+        self.jacobian_1.apply(bodies, self.impulse);
+        self.jacobian_2.apply(bodies, self.impulse);
+    }
+
+    pub(crate) fn solve(&mut self, bodies: &mut [Body]) {
+        if self.mass == 0. {
+            return;
+        }
+
+        let v = self.jacobian_1.velocity(bodies) + self.jacobian_2.velocity(bodies);
+        let impulse = -self.mass * v;
+
+        self.jacobian_1.apply(bodies, impulse);
+        self.jacobian_2.apply(bodies, impulse);
+        self.impulse += impulse;
+    }
+
+    pub(crate) fn correct_position(&mut self, bodies: &mut [Body]) {
+        if self.mass == 0. {
+            return;
+        }
+
+        let v = self.jacobian_1.integrated_velocity(bodies)
+            + self.jacobian_2.integrated_velocity(bodies);
+        let impulse = -self.mass * (v + self.correction);
+
+        self.jacobian_1.apply_pseudo(bodies, impulse);
+        self.jacobian_2.apply_pseudo(bodies, impulse);
+    }
+}
@@ -0,0 +1,541 @@
+//! Joints constrain the relative movement of bodies living in the world.<br>
+//! Bodies are referenced via their index in the world, just like in manifolds.<br>
+//!
+//! Available joints:
+//! - Revolute (hinge around a shared anchor, optionally angle limited)
+//! - Prismatic (slider along an axis, optionally translation limited)
+//! - Distance (keeps anchors at a fixed distance or within a range, e.g. ropes)
+//! - Pulley (two bodies hanging over two fixed ground anchors)
+//! - Gear (couples the coordinates of two revolute or prismatic joints)
+
+use std::fmt::Display;
+
+pub mod distance;
+pub mod gear;
+pub mod prismatic;
+pub mod pulley;
+pub mod revolute;
+
+pub use distance::DistanceJoint;
+pub use gear::GearJoint;
+pub use prismatic::PrismaticJoint;
+pub use pulley::PulleyJoint;
+pub use revolute::RevoluteJoint;
+
+use crate::{
+    entities::body::Body,
+    math::{
+        vector2::{cross, dot, ZERO},
+        Vector2,
+    },
+};
+
+#[derive(Clone, Debug)]
+pub enum Joint {
+    Revolute(RevoluteJoint),
+    Prismatic(PrismaticJoint),
+    Distance(DistanceJoint),
+    Pulley(PulleyJoint),
+    Gear(GearJoint),
+}
+
+impl Joint {
+    /// Indices of all bodies that are affected by this joint.
+    pub fn body_indices(&self) -> Vec<usize> {
+        use Joint::*;
+        match self {
+            Revolute(j) => vec![j.a_idx, j.b_idx],
+            Prismatic(j) => vec![j.a_idx, j.b_idx],
+            Distance(j) => vec![j.a_idx, j.b_idx],
+            Pulley(j) => vec![j.a_idx, j.b_idx],
+            Gear(j) => j.bodies.to_vec(),
+        }
+    }
+
+    /// The pair of bodies that should not collide with one another, if any.
+    pub fn connected_pair(&self) -> Option<(usize, usize)> {
+        use Joint::*;
+        let (a_idx, b_idx, collide_connected) = match self {
+            Revolute(j) => (j.a_idx, j.b_idx, j.collide_connected),
+            Prismatic(j) => (j.a_idx, j.b_idx, j.collide_connected),
+            Distance(j) => (j.a_idx, j.b_idx, j.collide_connected),
+            Pulley(j) => (j.a_idx, j.b_idx, j.collide_connected),
+            Gear(_) => return None,
+        };
+
+        if collide_connected {
+            return None;
+        }
+
+        Some((usize::min(a_idx, b_idx), usize::max(a_idx, b_idx)))
+    }
+
+    /// Coordinate (angle or translation) of revolute and prismatic joints,
+    /// together with the jacobian that describes how it changes.
+    pub(crate) fn coordinate(&self, bodies: &[Body]) -> Option<(f32, Jacobian)> {
+        match self {
+            Joint::Revolute(j) => Some(j.coordinate(bodies)),
+            Joint::Prismatic(j) => Some(j.coordinate(bodies)),
+            _ => None,
+        }
+    }
+
+    /// The correction factor defines which share of the positional error gets corrected each step.
+    pub(crate) fn setup(&mut self, bodies: &[Body], inv_dt: f32, correction_factor: f32) {
+        use Joint::*;
+        match self {
+            Revolute(j) => j.setup(bodies, inv_dt, correction_factor),
+            Prismatic(j) => j.setup(bodies, inv_dt, correction_factor),
+            Distance(j) => j.setup(bodies, inv_dt, correction_factor),
+            Pulley(j) => j.setup(bodies, inv_dt, correction_factor),
+            // gears are set up by the world, since they need access to other joints
+            Gear(_) => (),
+        }
+    }
+
+    /// Applies the impulses of the last step, so the solver does not have to start from scratch.
+    pub(crate) fn warm_start(&self, bodies: &mut [Body]) {
+        use Joint::*;
+        match self {
+            Revolute(j) => j.warm_start(bodies),
+            Prismatic(j) => j.warm_start(bodies),
+            Distance(j) => j.warm_start(bodies),
+            Pulley(j) => j.warm_start(bodies),
+            Gear(j) => j.warm_start(bodies),
+        }
+    }
+
+    pub(crate) fn solve(&mut self, bodies: &mut [Body]) {
+        use Joint::*;
+        match self {
+            Revolute(j) => j.solve(bodies),
+            Prismatic(j) => j.solve(bodies),
+            Distance(j) => j.solve(bodies),
+            Pulley(j) => j.solve(bodies),
+            Gear(j) => j.solve(bodies),
+        }
+    }
+
+    /// Pushes the bodies back towards a valid position via split impulses, just like contacts.<br>
+    /// Only the pseudo velocities change, so the correction never adds energy to the real velocities.
+    pub(crate) fn correct_position(&mut self, bodies: &mut [Body]) {
+        use Joint::*;
+        match self {
+            Revolute(j) => j.correct_position(bodies),
+            Prismatic(j) => j.correct_position(bodies),
+            Distance(j) => j.correct_position(bodies),
+            Pulley(j) => j.correct_position(bodies),
+            Gear(j) => j.correct_position(bodies),
+        }
+    }
+
+    /// Shifts all stored body indices after the body with the given index was removed.
+    pub(crate) fn body_removed(&mut self, removed_idx: usize) {
+        let shift = |idx: &mut usize| {
+            if *idx > removed_idx {
+                *idx -= 1;
+            }
+        };
+
+        use Joint::*;
+        match self {
+            Revolute(j) => [&mut j.a_idx, &mut j.b_idx].into_iter().for_each(shift),
+            Prismatic(j) => [&mut j.a_idx, &mut j.b_idx].into_iter().for_each(shift),
+            Distance(j) => [&mut j.a_idx, &mut j.b_idx].into_iter().for_each(shift),
+            Pulley(j) => [&mut j.a_idx, &mut j.b_idx].into_iter().for_each(shift),
+            Gear(j) => j.bodies.iter_mut().for_each(shift),
+        }
+    }
+
+    /// Shifts stored joint indices after the joint with the given index was removed.<br>
+    /// References to the removed joint itself get detached.
+    pub(crate) fn joint_removed(&mut self, removed_idx: usize) {
+        if let Joint::Gear(g) = self {
+            for idx in [&mut g.joint_1, &mut g.joint_2] {
+                if *idx == removed_idx {
+                    *idx = usize::MAX;
+                } else if *idx > removed_idx {
+                    *idx -= 1;
+                }
+            }
+        }
+    }
+
+    /// Joints that lost a joint they depend on cannot be solved anymore.
+    pub(crate) fn is_detached(&self) -> bool {
+        matches!(self, Joint::Gear(g) if g.joint_1 == usize::MAX || g.joint_2 == usize::MAX)
+    }
+}
+
+impl Display for Joint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Joint::*;
+        match self {
+            Revolute(j) => write!(f, "Revolute joint between {} and {}", j.a_idx, j.b_idx),
+            Prismatic(j) => write!(f, "Prismatic joint between {} and {}", j.a_idx, j.b_idx),
+            Distance(j) => write!(f, "Distance joint between {} and {}", j.a_idx, j.b_idx),
+            Pulley(j) => write!(f, "Pulley joint between {} and {}", j.a_idx, j.b_idx),
+            Gear(j) => write!(
+                f,
+                "Gear joint between joints {} and {}",
+                j.joint_1, j.joint_2
+            ),
+        }
+    }
+}
+
+// --------------------------------- JACOBIAN ---------------------------------
+/// Describes how a scalar constraint reacts to the velocities of 2 bodies.<br>
+/// Velocity of the constraint = dot(linear_a, v_a) + angular_a * w_a + dot(linear_b, v_b) + angular_b * w_b
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Jacobian {
+    pub a_idx: usize,
+    pub b_idx: usize,
+    pub linear_a: Vector2,
+    pub angular_a: f32,
+    pub linear_b: Vector2,
+    pub angular_b: f32,
+}
+
+impl Jacobian {
+    pub fn scaled(mut self, factor: f32) -> Jacobian {
+        self.linear_a *= factor;
+        self.angular_a *= factor;
+        self.linear_b *= factor;
+        self.angular_b *= factor;
+        self
+    }
+
+    pub fn velocity(&self, bodies: &[Body]) -> f32 {
+        let a = &bodies[self.a_idx].transform;
+        let b = &bodies[self.b_idx].transform;
+
+        dot(self.linear_a, a.velocity)
+            + self.angular_a * a.angular_velocity
+            + dot(self.linear_b, b.velocity)
+            + self.angular_b * b.angular_velocity
+    }
+
+    /// Velocity of the constraint the positions get integrated with, pseudo velocity included.
+    pub fn integrated_velocity(&self, bodies: &[Body]) -> f32 {
+        let a = &bodies[self.a_idx];
+        let b = &bodies[self.b_idx];
+
+        self.velocity(bodies)
+            + dot(self.linear_a, a.pseudo_velocity)
+            + self.angular_a * a.pseudo_angular_velocity
+            + dot(self.linear_b, b.pseudo_velocity)
+            + self.angular_b * b.pseudo_angular_velocity
+    }
+
+    /// Inverse of the effective mass, the constraint is inactive if this is zero.
+    pub fn inverse_mass(&self, bodies: &[Body]) -> f32 {
+        let a = &bodies[self.a_idx];
+        let b = &bodies[self.b_idx];
+
+        a.inverse_mass * self.linear_a.len_squared()
+            + a.inverse_inertia * self.angular_a * self.angular_a
+            + b.inverse_mass * self.linear_b.len_squared()
+            + b.inverse_inertia * self.angular_b * self.angular_b
+    }
+
+    pub fn apply(&self, bodies: &mut [Body], impulse: f32) {
+        let a = &mut bodies[self.a_idx];
+        a.transform.velocity += self.linear_a * (impulse * a.inverse_mass);
+        a.transform.angular_velocity += self.angular_a * impulse * a.inverse_inertia;
+
+        let b = &mut bodies[self.b_idx];
+        b.transform.velocity += self.linear_b * (impulse * b.inverse_mass);
+        b.transform.angular_velocity += self.angular_b * impulse * b.inverse_inertia;
+    }
+
+    pub fn apply_pseudo(&self, bodies: &mut [Body], impulse: f32) {
+        let a = &mut bodies[self.a_idx];
+        a.pseudo_velocity += self.linear_a * (impulse * a.inverse_mass);
+        a.pseudo_angular_velocity += self.angular_a * impulse * a.inverse_inertia;
+
+        let b = &mut bodies[self.b_idx];
+        b.pseudo_velocity += self.linear_b * (impulse * b.inverse_mass);
+        b.pseudo_angular_velocity += self.angular_b * impulse * b.inverse_inertia;
+    }
+
+    /// Solves an inequality constraint (C >= 0), the accumulated impulse may only push.
+    pub fn solve_limit(&self, bodies: &mut [Body], mass: f32, bias: f32, accumulated: &mut f32) {
+        let impulse = clamp_accumulated(-mass * (self.velocity(bodies) + bias), accumulated);
+        self.apply(bodies, impulse);
+    }
+
+    /// Position correction of an inequality constraint (C >= 0), the accumulated impulse may only push.
+    pub fn correct_limit(&self, bodies: &mut [Body], mass: f32, bias: f32, accumulated: &mut f32) {
+        let impulse = clamp_accumulated(
+            -mass * (self.integrated_velocity(bodies) + bias),
+            accumulated,
+        );
+        self.apply_pseudo(bodies, impulse);
+    }
+}
+
+/// Adds the impulse to the accumulated one, which may not drop below zero.<br>
+/// Returns the share of the impulse that still has to be applied.
+fn clamp_accumulated(impulse: f32, accumulated: &mut f32) -> f32 {
+    let clamped = f32::max(*accumulated + impulse, 0.);
+    let applied = clamped - *accumulated;
+    *accumulated = clamped;
+    applied
+}
+
+// --------------------------------- UTILITY FUNCTIONS ---------------------------------
+/// Rotates a body-local vector into world orientation.
+pub(crate) fn to_world(body: &Body, local: Vector2) -> Vector2 {
+    Vector2::rotated(local, body.transform.rotation)
+}
+
+/// Rotates a world vector into body-local orientation.
+pub(crate) fn to_local(body: &Body, world: Vector2) -> Vector2 {
+    Vector2::rotated(world, -body.transform.rotation)
+}
+
+pub(crate) fn velocity_at(body: &Body, r: Vector2) -> Vector2 {
+    body.transform.velocity + cross(body.transform.angular_velocity, r)
+}
+
+pub(crate) fn apply_impulse_at(body: &mut Body, impulse: Vector2, r: Vector2) {
+    body.transform.velocity += impulse * body.inverse_mass;
+    body.transform.angular_velocity += r.crossed(impulse) * body.inverse_inertia;
+}
+
+pub(crate) fn apply_pseudo_impulse_at(body: &mut Body, impulse: Vector2, r: Vector2) {
+    body.pseudo_velocity += impulse * body.inverse_mass;
+    body.pseudo_angular_velocity += r.crossed(impulse) * body.inverse_inertia;
+}
+
+pub(crate) fn inverse_or_zero(f: f32) -> f32 {
+    if f == 0.0 {
+        return 0.0;
+    }
+
+    1.0 / f
+}
+
+/// Inactive limits allow the bodies to approach the limit within a single step (speculative).<br>
+/// Violated limits only stop the bodies, pushing them back is left to the position correction.
+pub(crate) fn limit_bias(c: f32, inv_dt: f32) -> f32 {
+    f32::max(c, 0.) * inv_dt
+}
+
+/// Bias of the position correction, which removes the given share of the error each step.
+pub(crate) fn correction_bias(c: f32, correction_factor: f32, inv_dt: f32) -> f32 {
+    c * correction_factor * inv_dt
+}
+
+/// Bias of the position correction for limits, inactive limits stay speculative.
+pub(crate) fn limit_correction_bias(c: f32, correction_factor: f32, inv_dt: f32) -> f32 {
+    if c > 0. {
+        return c * inv_dt;
+    }
+
+    correction_bias(c, correction_factor, inv_dt)
+}
+
+/// Solves the 2x2 linear system k * x = rhs, where k is given column-wise.
+pub(crate) fn solve_2x2(col_1: Vector2, col_2: Vector2, rhs: Vector2) -> Vector2 {
+    let det = col_1.x * col_2.y - col_2.x * col_1.y;
+
+    if det == 0.0 {
+        return ZERO;
+    }
+
+    let inv_det = 1.0 / det;
+
+    Vector2::new(
+        inv_det * (col_2.y * rhs.x - col_2.x * rhs.y),
+        inv_det * (col_1.x * rhs.y - col_1.y * rhs.x),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::material::DEFAULT,
+        environment::world::World,
+        test_utils::{run, world},
+    };
+
+    #[test]
+    fn add_joint_rejects_unknown_bodies_and_joints() {
+        let mut world = world();
+        let a = world.add_body(Body::platform_circle(0., 0., 0.1, DEFAULT));
+        let b = world.add_body(Body::circle(1., 0., 0.2, DEFAULT));
+
+        let bodies = world.get_bodies();
+        let mut joint = RevoluteJoint::new(&bodies[a], a, &bodies[b], b, ZERO);
+        joint.b_idx = 7;
+        assert_eq!(world.add_joint(Joint::Revolute(joint)), None);
+
+        let bodies = world.get_bodies();
+        let joint = RevoluteJoint::new(&bodies[a], a, &bodies[b], b, ZERO);
+        let hinge = world.add_joint(Joint::Revolute(joint)).unwrap();
+
+        let mut gear =
+            GearJoint::new(world.get_joints(), world.get_bodies(), hinge, hinge, 1.).unwrap();
+        gear.joint_2 = 3;
+        assert_eq!(world.add_joint(Joint::Gear(gear)), None);
+
+        assert_eq!(world.get_joints().len(), 1);
+        run(&mut world, 10);
+    }
+
+    #[test]
+    fn joint_position_correction_does_not_add_velocity() {
+        let mut world = World::new(60., 10.);
+        let a = world.add_body(Body::circle(0., 0., 0.5, DEFAULT));
+        let b = world.add_body(Body::circle(3., 0., 0.5, DEFAULT));
+
+        let bodies = world.get_bodies();
+        let mut joint = RevoluteJoint::new(&bodies[a], a, &bodies[b], b, Vector2::new(1.5, 0.));
+        joint.local_anchor_b = Vector2::new(-1., 0.);
+        world.add_joint(Joint::Revolute(joint)).unwrap();
+
+        run(&mut world, 60);
+
+        let bodies = world.get_bodies();
+        let distance = (bodies[b].transform.location - bodies[a].transform.location).len();
+
+        assert!((distance - 2.5).abs() < 0.01, "bodies are {distance} apart");
+        assert!(bodies[a].transform.velocity.len() < 0.01);
+        assert!(bodies[b].transform.velocity.len() < 0.01);
+    }
+
+    #[test]
+    fn revolute_joint_keeps_pendulum_at_its_anchor() {
+        let mut world = world();
+        let pivot = world.add_body(Body::platform_circle(0., 0., 0.1, DEFAULT));
+        let bob = world.add_body(Body::circle(2., 0., 0.2, DEFAULT));
+
+        let bodies = world.get_bodies();
+        let joint = RevoluteJoint::new(&bodies[pivot], pivot, &bodies[bob], bob, ZERO);
+        world.add_joint(Joint::Revolute(joint)).unwrap();
+
+        let mut lowest = 0f32;
+
+        for _ in 0..120 {
+            world.update();
+            let location = world.get_bodies()[bob].transform.location;
+            lowest = lowest.min(location.y);
+            assert!((location.len() - 2.).abs() < 0.05, "pendulum at {location}");
+        }
+
+        assert!(lowest < -1.9);
+    }
+
+    #[test]
+    fn revolute_limit_stops_rotation() {
+        let mut world = world();
+        let pivot = world.add_body(Body::platform_circle(0., 0., 0.1, DEFAULT));
+        let arm = world.add_body(Body::obb(1., 0., 2., 0.2, DEFAULT));
+
+        let bodies = world.get_bodies();
+        let joint =
+            RevoluteJoint::new(&bodies[pivot], pivot, &bodies[arm], arm, ZERO).limit(-0.5, 0.5);
+        let joint_idx = world.add_joint(Joint::Revolute(joint)).unwrap();
+
+        run(&mut world, 120);
+
+        let Joint::Revolute(joint) = &world.get_joints()[joint_idx] else {
+            unreachable!();
+        };
+        let angle = joint.get_angle(world.get_bodies());
+        assert!((angle + 0.5).abs() < 0.05, "angle {angle}");
+    }
+
+    #[test]
+    fn prismatic_joint_only_allows_movement_along_its_axis() {
+        let mut world = world();
+        let ground = world.add_body(Body::platform_circle(0., 0., 0.1, DEFAULT));
+        let slider = world.add_body(Body::aabb(0., 0., 0.5, 0.5, DEFAULT));
+
+        let bodies = world.get_bodies();
+        let axis = Vector2::new(1., -1.);
+        let joint =
+            PrismaticJoint::new(&bodies[ground], ground, &bodies[slider], slider, ZERO, axis);
+        world.add_joint(Joint::Prismatic(joint)).unwrap();
+
+        run(&mut world, 60);
+
+        let location = world.get_bodies()[slider].transform.location;
+        assert!(location.y < -1.);
+        assert!(
+            (location.x + location.y).abs() < 0.05,
+            "left the axis: {location}"
+        );
+    }
+
+    #[test]
+    fn pulley_joint_lifts_the_lighter_body() {
+        let mut world = world();
+        let light = world.add_body(Body::aabb(-2., 5., 0.5, 0.5, DEFAULT));
+        let heavy = world.add_body(Body::aabb(2., 5., 1., 1., DEFAULT));
+
+        let bodies = world.get_bodies();
+        let joint = PulleyJoint::new(
+            &bodies[light],
+            light,
+            &bodies[heavy],
+            heavy,
+            Vector2::new(-2., 8.),
+            Vector2::new(2., 8.),
+            Vector2::new(-2., 5.),
+            Vector2::new(2., 5.),
+            1.,
+        );
+        let total_length = joint.total_length;
+        let joint_idx = world.add_joint(Joint::Pulley(joint)).unwrap();
+
+        run(&mut world, 60);
+
+        let bodies = world.get_bodies();
+        assert!(bodies[light].transform.location.y > 5.5);
+        assert!(bodies[heavy].transform.location.y < 4.5);
+
+        let Joint::Pulley(joint) = &world.get_joints()[joint_idx] else {
+            unreachable!();
+        };
+        let (length_a, length_b) = joint.get_lengths(bodies);
+        assert!((length_a + length_b - total_length).abs() < 0.05);
+    }
+
+    #[test]
+    fn gear_joint_couples_two_wheels() {
+        let mut world = World::new(60., 10.);
+        let ground = world.add_body(Body::platform_circle(0., -5., 0.1, DEFAULT));
+        let mut spinning = Body::circle(0., 0., 1., DEFAULT);
+        spinning.transform.angular_velocity = 1.;
+        let wheel_1 = world.add_body(spinning);
+        let wheel_2 = world.add_body(Body::circle(3., 0., 0.5, DEFAULT));
+
+        let hinge = |world: &mut World, body_idx: usize| {
+            let bodies = world.get_bodies();
+            let anchor = bodies[body_idx].transform.location;
+            let joint =
+                RevoluteJoint::new(&bodies[ground], ground, &bodies[body_idx], body_idx, anchor);
+            world.add_joint(Joint::Revolute(joint)).unwrap()
+        };
+
+        let hinge_1 = hinge(&mut world, wheel_1);
+        let hinge_2 = hinge(&mut world, wheel_2);
+
+        let gear =
+            GearJoint::new(world.get_joints(), world.get_bodies(), hinge_1, hinge_2, 2.).unwrap();
+        world.add_joint(Joint::Gear(gear)).unwrap();
+
+        run(&mut world, 30);
+
+        let bodies = world.get_bodies();
+        let w_1 = bodies[wheel_1].transform.angular_velocity;
+        let w_2 = bodies[wheel_2].transform.angular_velocity;
+
+        assert!(w_1.abs() > 0.1);
+        assert!((w_1 + 2. * w_2).abs() < 0.01, "w_1 {w_1}, w_2 {w_2}");
+    }
+}
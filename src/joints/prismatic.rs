@@ -0,0 +1,225 @@
+//! Prismatic joint, lets two bodies slide along a shared axis without rotating relative to each other.<br>
+//! The axis is fixed to the first body, the translation along it can be limited.
+
+use crate::{
+    entities::body::Body,
+    joints::{
+        correction_bias, inverse_or_zero, limit_bias, limit_correction_bias, to_local, to_world,
+        Jacobian,
+    },
+    math::{
+        vector2::{NORMAL_RIGHT, ZERO},
+        Vector2,
+    },
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct PrismaticJoint {
+    pub a_idx: usize,
+    pub b_idx: usize,
+    pub local_anchor_a: Vector2,
+    pub local_anchor_b: Vector2,
+    pub local_axis: Vector2,
+    pub reference_angle: f32,
+    pub enable_limit: bool,
+    pub lower_translation: f32,
+    pub upper_translation: f32,
+    pub collide_connected: bool,
+
+    // recalculated by setup every step
+    perpendicular: Jacobian,
+    perpendicular_mass: f32,
+    perpendicular_correction: f32,
+    angular: Jacobian,
+    angular_mass: f32,
+    angular_correction: f32,
+    axial: Jacobian,
+    axial_mass: f32,
+    lower_bias: f32,
+    upper_bias: f32,
+    lower_correction: f32,
+    upper_correction: f32,
+
+    // accumulated impulses, kept across steps for warm starting
+    perpendicular_impulse: f32,
+    angular_impulse: f32,
+    lower_impulse: f32,
+    upper_impulse: f32,
+
+    // accumulated pseudo impulses of the position correction, reset every step
+    lower_pseudo_impulse: f32,
+    upper_pseudo_impulse: f32,
+}
+
+impl PrismaticJoint {
+    /// Creates a prismatic joint between two bodies.<br>
+    /// Anchor and axis are given in world coordinates, the axis does not need to be normalized.
+    pub fn new(
+        a: &Body,
+        a_idx: usize,
+        b: &Body,
+        b_idx: usize,
+        anchor: Vector2,
+        axis: Vector2,
+    ) -> PrismaticJoint {
+        PrismaticJoint {
+            a_idx,
+            b_idx,
+            local_anchor_a: to_local(a, anchor - a.transform.location),
+            local_anchor_b: to_local(b, anchor - b.transform.location),
+            local_axis: to_local(a, axis.normalize().unwrap_or(NORMAL_RIGHT)),
+            reference_angle: b.transform.rotation - a.transform.rotation,
+            ..Default::default()
+        }
+    }
+
+    /// Limits the translation along the axis to the given range.<br>
+    /// The translation is measured relative to the anchor on creation.
+    pub fn limit(mut self, lower_translation: f32, upper_translation: f32) -> Self {
+        self.enable_limit = true;
+        self.lower_translation = f32::min(lower_translation, upper_translation);
+        self.upper_translation = f32::max(lower_translation, upper_translation);
+        self
+    }
+
+    pub fn collide_connected(mut self, collide_connected: bool) -> Self {
+        self.collide_connected = collide_connected;
+        self
+    }
+}
+
+impl PrismaticJoint {
+    pub fn get_translation(&self, bodies: &[Body]) -> f32 {
+        self.coordinate(bodies).0
+    }
+
+    /// Projection of the anchor distance onto the given axis (in world orientation).
+    fn projection(&self, bodies: &[Body], axis: Vector2) -> (f32, Jacobian) {
+        let a = &bodies[self.a_idx];
+        let b = &bodies[self.b_idx];
+
+        let ra = to_world(a, self.local_anchor_a);
+        let rb = to_world(b, self.local_anchor_b);
+        let d = (b.transform.location + rb) - (a.transform.location + ra);
+
+        let jacobian = Jacobian {
+            a_idx: self.a_idx,
+            b_idx: self.b_idx,
+            linear_a: ZERO - axis,
+            angular_a: -(d + ra).crossed(axis),
+            linear_b: axis,
+            angular_b: rb.crossed(axis),
+        };
+
+        (d.dotted(axis), jacobian)
+    }
+
+    pub(crate) fn coordinate(&self, bodies: &[Body]) -> (f32, Jacobian) {
+        let axis = to_world(&bodies[self.a_idx], self.local_axis);
+        self.projection(bodies, axis)
+    }
+
+    pub(crate) fn setup(&mut self, bodies: &[Body], inv_dt: f32, correction_factor: f32) {
+        let axis = to_world(&bodies[self.a_idx], self.local_axis);
+
+        let (offset, perpendicular) = self.projection(bodies, axis.tangent());
+        self.perpendicular = perpendicular;
+        self.perpendicular_mass = inverse_or_zero(perpendicular.inverse_mass(bodies));
+        self.perpendicular_correction = correction_bias(offset, correction_factor, inv_dt);
+
+        let angle = bodies[self.b_idx].transform.rotation
+            - bodies[self.a_idx].transform.rotation
+            - self.reference_angle;
+
+        self.angular = Jacobian {
+            a_idx: self.a_idx,
+            b_idx: self.b_idx,
+            angular_a: -1.,
+            angular_b: 1.,
+            ..Default::default()
+        };
+        self.angular_mass = inverse_or_zero(self.angular.inverse_mass(bodies));
+        self.angular_correction = correction_bias(angle, correction_factor, inv_dt);
+
+        let (translation, axial) = self.projection(bodies, axis);
+        self.axial = axial;
+        self.axial_mass = inverse_or_zero(axial.inverse_mass(bodies));
+        self.lower_bias = limit_bias(translation - self.lower_translation, inv_dt);
+        self.upper_bias = limit_bias(self.upper_translation - translation, inv_dt);
+        self.lower_correction = limit_correction_bias(
+            translation - self.lower_translation,
+            correction_factor,
+            inv_dt,
+        );
+        self.upper_correction = limit_correction_bias(
+            self.upper_translation - translation,
+            correction_factor,
+            inv_dt,
+        );
+
+        self.lower_pseudo_impulse = 0.;
+        self.upper_pseudo_impulse = 0.;
+    }
+
+    pub(crate) fn warm_start(&self, bodies: &mut [Body]) {
+        self.perpendicular.apply(bodies, self.perpendicular_impulse);
+        self.angular.apply(bodies, self.angular_impulse);
+        self.axial
+            .apply(bodies, self.lower_impulse - self.upper_impulse);
+    }
+
+    pub(crate) fn solve(&mut self, bodies: &mut [Body]) {
+        if self.enable_limit && self.axial_mass != 0. {
+            // lower limit, translation - lower >= 0
+            self.axial.solve_limit(
+                bodies,
+                self.axial_mass,
+                self.lower_bias,
+                &mut self.lower_impulse,
+            );
+
+            // upper limit, upper - translation >= 0
+            self.axial.scaled(-1.).solve_limit(
+                bodies,
+                self.axial_mass,
+                self.upper_bias,
+                &mut self.upper_impulse,
+            );
+        }
+
+        let impulse = -self.angular_mass * self.angular.velocity(bodies);
+        self.angular.apply(bodies, impulse);
+        self.angular_impulse += impulse;
+
+        let impulse = -self.perpendicular_mass * self.perpendicular.velocity(bodies);
+        self.perpendicular.apply(bodies, impulse);
+        self.perpendicular_impulse += impulse;
+    }
+
+    /// Pushes the bodies back onto the axis, into the reference angle and into the limited range.
+    pub(crate) fn correct_position(&mut self, bodies: &mut [Body]) {
+        if self.enable_limit && self.axial_mass != 0. {
+            self.axial.correct_limit(
+                bodies,
+                self.axial_mass,
+                self.lower_correction,
+                &mut self.lower_pseudo_impulse,
+            );
+
+            self.axial.scaled(-1.).correct_limit(
+                bodies,
+                self.axial_mass,
+                self.upper_correction,
+                &mut self.upper_pseudo_impulse,
+            );
+        }
+
+        let impulse = -self.angular_mass
+            * (self.angular.integrated_velocity(bodies) + self.angular_correction);
+        self.angular.apply_pseudo(bodies, impulse);
+
+        let impulse = -self.perpendicular_mass
+            * (self.perpendicular.integrated_velocity(bodies) + self.perpendicular_correction);
+        self.perpendicular.apply_pseudo(bodies, impulse);
+    }
+}
@@ -0,0 +1,136 @@
+//! Pulley joint, connects two bodies via a rope that runs over two fixed ground anchors.<br>
+//! length_a + ratio * length_b stays constant, so one body goes up when the other one goes down.
+
+use crate::{
+    entities::body::Body,
+    joints::{correction_bias, inverse_or_zero, to_local, to_world, Jacobian},
+    math::{vector2::ZERO, Vector2},
+};
+
+/// Segments shorter than this do not have a defined direction anymore.
+const MIN_SEGMENT_LENGTH: f32 = 0.005;
+
+#[derive(Clone, Debug, Default)]
+pub struct PulleyJoint {
+    pub a_idx: usize,
+    pub b_idx: usize,
+    pub ground_anchor_a: Vector2,
+    pub ground_anchor_b: Vector2,
+    pub local_anchor_a: Vector2,
+    pub local_anchor_b: Vector2,
+    pub ratio: f32,
+    pub total_length: f32,
+    pub collide_connected: bool,
+
+    // recalculated by setup every step
+    jacobian: Jacobian,
+    mass: f32,
+    correction: f32,
+
+    // accumulated impulses, kept across steps for warm starting
+    impulse: f32,
+}
+
+impl PulleyJoint {
+    /// Creates a pulley joint, where the anchors on the bodies are given in world coordinates.<br>
+    /// The ratio gets clamped to a small positive value, since a ratio of zero is no pulley.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        a: &Body,
+        a_idx: usize,
+        b: &Body,
+        b_idx: usize,
+        ground_anchor_a: Vector2,
+        ground_anchor_b: Vector2,
+        anchor_a: Vector2,
+        anchor_b: Vector2,
+        ratio: f32,
+    ) -> PulleyJoint {
+        let ratio = f32::max(ratio, f32::EPSILON);
+        let length_a = (anchor_a - ground_anchor_a).len();
+        let length_b = (anchor_b - ground_anchor_b).len();
+
+        PulleyJoint {
+            a_idx,
+            b_idx,
+            ground_anchor_a,
+            ground_anchor_b,
+            local_anchor_a: to_local(a, anchor_a - a.transform.location),
+            local_anchor_b: to_local(b, anchor_b - b.transform.location),
+            ratio,
+            total_length: length_a + ratio * length_b,
+            ..Default::default()
+        }
+    }
+
+    pub fn collide_connected(mut self, collide_connected: bool) -> Self {
+        self.collide_connected = collide_connected;
+        self
+    }
+}
+
+impl PulleyJoint {
+    /// Current rope lengths on both sides of the pulley.
+    pub fn get_lengths(&self, bodies: &[Body]) -> (f32, f32) {
+        let (_, _, length_a, _, _, length_b) = self.segments(bodies);
+        (length_a, length_b)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn segments(&self, bodies: &[Body]) -> (Vector2, Vector2, f32, Vector2, Vector2, f32) {
+        let a = &bodies[self.a_idx];
+        let b = &bodies[self.b_idx];
+
+        let ra = to_world(a, self.local_anchor_a);
+        let rb = to_world(b, self.local_anchor_b);
+
+        let ua = a.transform.location + ra - self.ground_anchor_a;
+        let ub = b.transform.location + rb - self.ground_anchor_b;
+
+        (ra, ua, ua.len(), rb, ub, ub.len())
+    }
+
+    pub(crate) fn setup(&mut self, bodies: &[Body], inv_dt: f32, correction_factor: f32) {
+        let (ra, ua, length_a, rb, ub, length_b) = self.segments(bodies);
+
+        let ua = if length_a > MIN_SEGMENT_LENGTH {
+            ua * (1. / length_a)
+        } else {
+            ZERO
+        };
+        let ub = if length_b > MIN_SEGMENT_LENGTH {
+            ub * (1. / length_b)
+        } else {
+            ZERO
+        };
+
+        // C = total - length_a - ratio * length_b = 0
+        self.jacobian = Jacobian {
+            a_idx: self.a_idx,
+            b_idx: self.b_idx,
+            linear_a: ZERO - ua,
+            angular_a: -ra.crossed(ua),
+            linear_b: ub * -self.ratio,
+            angular_b: -self.ratio * rb.crossed(ub),
+        };
+        self.mass = inverse_or_zero(self.jacobian.inverse_mass(bodies));
+
+        let error = self.total_length - length_a - self.ratio * length_b;
+        self.correction = correction_bias(error, correction_factor, inv_dt);
+    }
+
+    pub(crate) fn warm_start(&self, bodies: &mut [Body]) {
+        self.jacobian.apply(bodies, self.impulse);
+    }
+
+    pub(crate) fn solve(&mut self, bodies: &mut [Body]) {
+        let impulse = -self.mass * self.jacobian.velocity(bodies);
+        self.jacobian.apply(bodies, impulse);
+        self.impulse += impulse;
+    }
+
+    pub(crate) fn correct_position(&mut self, bodies: &mut [Body]) {
+        let impulse = -self.mass * (self.jacobian.integrated_velocity(bodies) + self.correction);
+        self.jacobian.apply_pseudo(bodies, impulse);
+    }
+}
@@ -0,0 +1,220 @@
+//! Revolute joint, pins two bodies together at a shared anchor point.<br>
+//! The bodies can rotate freely around the anchor, unless the relative angle is limited.
+
+use crate::{
+    entities::body::Body,
+    joints::{
+        apply_impulse_at, apply_pseudo_impulse_at, inverse_or_zero, limit_bias,
+        limit_correction_bias, solve_2x2, to_local, to_world, velocity_at, Jacobian,
+    },
+    math::{vector2::ZERO, Vector2},
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct RevoluteJoint {
+    pub a_idx: usize,
+    pub b_idx: usize,
+    pub local_anchor_a: Vector2,
+    pub local_anchor_b: Vector2,
+    pub reference_angle: f32,
+    pub enable_limit: bool,
+    pub lower_angle: f32,
+    pub upper_angle: f32,
+    pub collide_connected: bool,
+
+    // recalculated by setup every step
+    r_a: Vector2,
+    r_b: Vector2,
+    k_col_1: Vector2,
+    k_col_2: Vector2,
+    delta_time: f32,
+    correction_factor: f32,
+    angular: Jacobian,
+    angular_mass: f32,
+    lower_bias: f32,
+    upper_bias: f32,
+    lower_correction: f32,
+    upper_correction: f32,
+
+    // accumulated impulses, kept across steps for warm starting
+    impulse: Vector2,
+    lower_impulse: f32,
+    upper_impulse: f32,
+
+    // accumulated pseudo impulses of the position correction, reset every step
+    lower_pseudo_impulse: f32,
+    upper_pseudo_impulse: f32,
+}
+
+impl RevoluteJoint {
+    /// Creates a revolute joint between two bodies around the given anchor in world coordinates.
+    pub fn new(a: &Body, a_idx: usize, b: &Body, b_idx: usize, anchor: Vector2) -> RevoluteJoint {
+        RevoluteJoint {
+            a_idx,
+            b_idx,
+            local_anchor_a: to_local(a, anchor - a.transform.location),
+            local_anchor_b: to_local(b, anchor - b.transform.location),
+            reference_angle: b.transform.rotation - a.transform.rotation,
+            ..Default::default()
+        }
+    }
+
+    /// Limits the relative angle of the bodies to the given range in radians.<br>
+    /// The angle is measured relative to the angle the bodies had on creation.
+    pub fn limit(mut self, lower_angle: f32, upper_angle: f32) -> Self {
+        self.enable_limit = true;
+        self.lower_angle = f32::min(lower_angle, upper_angle);
+        self.upper_angle = f32::max(lower_angle, upper_angle);
+        self
+    }
+
+    pub fn collide_connected(mut self, collide_connected: bool) -> Self {
+        self.collide_connected = collide_connected;
+        self
+    }
+}
+
+impl RevoluteJoint {
+    pub fn get_angle(&self, bodies: &[Body]) -> f32 {
+        bodies[self.b_idx].transform.rotation
+            - bodies[self.a_idx].transform.rotation
+            - self.reference_angle
+    }
+
+    pub(crate) fn coordinate(&self, bodies: &[Body]) -> (f32, Jacobian) {
+        let jacobian = Jacobian {
+            a_idx: self.a_idx,
+            b_idx: self.b_idx,
+            linear_a: ZERO,
+            angular_a: -1.,
+            linear_b: ZERO,
+            angular_b: 1.,
+        };
+
+        (self.get_angle(bodies), jacobian)
+    }
+
+    pub(crate) fn setup(&mut self, bodies: &[Body], inv_dt: f32, correction_factor: f32) {
+        let a = &bodies[self.a_idx];
+        let b = &bodies[self.b_idx];
+
+        self.r_a = to_world(a, self.local_anchor_a);
+        self.r_b = to_world(b, self.local_anchor_b);
+
+        (self.k_col_1, self.k_col_2) = point_mass(a, b, self.r_a, self.r_b);
+
+        self.delta_time = 1. / inv_dt;
+        self.correction_factor = correction_factor;
+
+        let (angle, angular) = self.coordinate(bodies);
+        self.angular = angular;
+        self.angular_mass = inverse_or_zero(a.inverse_inertia + b.inverse_inertia);
+        self.lower_bias = limit_bias(angle - self.lower_angle, inv_dt);
+        self.upper_bias = limit_bias(self.upper_angle - angle, inv_dt);
+        self.lower_correction =
+            limit_correction_bias(angle - self.lower_angle, correction_factor, inv_dt);
+        self.upper_correction =
+            limit_correction_bias(self.upper_angle - angle, correction_factor, inv_dt);
+
+        self.lower_pseudo_impulse = 0.;
+        self.upper_pseudo_impulse = 0.;
+    }
+
+    pub(crate) fn warm_start(&self, bodies: &mut [Body]) {
+        apply_impulse_at(&mut bodies[self.a_idx], ZERO - self.impulse, self.r_a);
+        apply_impulse_at(&mut bodies[self.b_idx], self.impulse, self.r_b);
+
+        self.angular
+            .apply(bodies, self.lower_impulse - self.upper_impulse);
+    }
+
+    pub(crate) fn solve(&mut self, bodies: &mut [Body]) {
+        if self.enable_limit && self.angular_mass != 0. {
+            // lower limit, angle - lower >= 0
+            self.angular.solve_limit(
+                bodies,
+                self.angular_mass,
+                self.lower_bias,
+                &mut self.lower_impulse,
+            );
+
+            // upper limit, upper - angle >= 0
+            self.angular.scaled(-1.).solve_limit(
+                bodies,
+                self.angular_mass,
+                self.upper_bias,
+                &mut self.upper_impulse,
+            );
+        }
+
+        let v_rel =
+            velocity_at(&bodies[self.b_idx], self.r_b) - velocity_at(&bodies[self.a_idx], self.r_a);
+
+        let impulse = solve_2x2(self.k_col_1, self.k_col_2, ZERO - v_rel);
+        self.impulse += impulse;
+
+        apply_impulse_at(&mut bodies[self.a_idx], ZERO - impulse, self.r_a);
+        apply_impulse_at(&mut bodies[self.b_idx], impulse, self.r_b);
+    }
+
+    /// Pushes the anchors back together and limited joints back into their range.
+    pub(crate) fn correct_position(&mut self, bodies: &mut [Body]) {
+        if self.enable_limit && self.angular_mass != 0. {
+            self.angular.correct_limit(
+                bodies,
+                self.angular_mass,
+                self.lower_correction,
+                &mut self.lower_pseudo_impulse,
+            );
+
+            self.angular.scaled(-1.).correct_limit(
+                bodies,
+                self.angular_mass,
+                self.upper_correction,
+                &mut self.upper_pseudo_impulse,
+            );
+        }
+
+        // the anchors are compared where the bodies end up after this step, since fast spinning
+        // bodies would drift apart again otherwise, e.g. the links of a rope that hits the ground
+        let (location_a, r_a) = self.predict(&bodies[self.a_idx], self.local_anchor_a);
+        let (location_b, r_b) = self.predict(&bodies[self.b_idx], self.local_anchor_b);
+        let error = (location_b + r_b) - (location_a + r_a);
+
+        let (k_col_1, k_col_2) = point_mass(&bodies[self.a_idx], &bodies[self.b_idx], r_a, r_b);
+        let impulse = solve_2x2(
+            k_col_1,
+            k_col_2,
+            error * (-self.correction_factor / self.delta_time),
+        );
+
+        apply_pseudo_impulse_at(&mut bodies[self.a_idx], ZERO - impulse, r_a);
+        apply_pseudo_impulse_at(&mut bodies[self.b_idx], impulse, r_b);
+    }
+
+    /// Location of the body and the given anchor in world orientation after integrating this step.
+    fn predict(&self, body: &Body, local_anchor: Vector2) -> (Vector2, Vector2) {
+        let velocity = body.transform.velocity + body.pseudo_velocity;
+        let angular_velocity = body.transform.angular_velocity + body.pseudo_angular_velocity;
+        let rotation = body.transform.rotation + angular_velocity * self.delta_time;
+
+        (
+            body.transform.location + velocity * self.delta_time,
+            Vector2::rotated(local_anchor, rotation),
+        )
+    }
+}
+
+/// Columns of the matrix that maps an impulse at both anchors to their relative velocity.
+fn point_mass(a: &Body, b: &Body, ra: Vector2, rb: Vector2) -> (Vector2, Vector2) {
+    let (ma, mb) = (a.inverse_mass, b.inverse_mass);
+    let (ia, ib) = (a.inverse_inertia, b.inverse_inertia);
+
+    let k_col_1 = Vector2::new(
+        ma + mb + ia * ra.y * ra.y + ib * rb.y * rb.y,
+        -ia * ra.x * ra.y - ib * rb.x * rb.y,
+    );
+    let k_col_2 = Vector2::new(k_col_1.y, ma + mb + ia * ra.x * ra.x + ib * rb.x * rb.x);
+
+    (k_col_1, k_col_2)
+}
@@ -1,6 +1,10 @@
 pub mod collision;
 pub mod entities;
 pub mod environment;
+pub mod joints;
 pub mod math;
 pub mod prelude;
 pub mod shapes;
+
+#[cfg(test)]
+mod test_utils;
@@ -2,8 +2,13 @@ pub use crate::collision::*;
 pub use crate::entities::attractor::*;
 pub use crate::entities::body::*;
 pub use crate::entities::material::*;
+pub use crate::entities::ragdoll::*;
+pub use crate::entities::rope::*;
 pub use crate::entities::transform::*;
+pub use crate::environment::events::*;
+pub use crate::environment::explosion::*;
 pub use crate::environment::force::*;
 pub use crate::environment::world::*;
+pub use crate::joints::*;
 pub use crate::math::*;
 pub use crate::shapes::*;
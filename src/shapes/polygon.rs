@@ -102,15 +102,11 @@ impl Polygon {
                         state = 1;
                     }
                 }
-                1 => {
-                    if crossproduct < 0.0 {
-                        return false;
-                    }
+                1 if crossproduct < 0.0 => {
+                    return false;
                 }
-                -1 => {
-                    if crossproduct > 0.0 {
-                        return false;
-                    }
+                -1 if crossproduct > 0.0 => {
+                    return false;
                 }
                 _ => (),
             }
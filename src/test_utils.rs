@@ -0,0 +1,62 @@
+//! Helpers shared by the unit tests of all modules.
+
+use crate::{
+    entities::{
+        body::Body,
+        material::{Material, DEFAULT},
+    },
+    environment::{force::GRAVITY_EARTH, world::World},
+};
+
+/// World running at 60 ticks per second, 10 pixels per meter and earth gravity.
+pub(crate) fn world() -> World {
+    let mut world = World::new(60., 10.);
+    world.add_force(GRAVITY_EARTH);
+    world
+}
+
+pub(crate) fn run(world: &mut World, steps: usize) {
+    for _ in 0..steps {
+        world.update();
+    }
+}
+
+/// Static ground with its top at y = 0 and a box resting on it, which had time to fall asleep.
+pub(crate) fn sleeping_box() -> (World, usize, usize) {
+    let mut world = world();
+    let ground = world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+    let body = world.add_body(Body::aabb(0., 0.5, 1., 1., DEFAULT));
+
+    run(&mut world, 180);
+    assert!(world.get_bodies()[body].is_sleeping());
+
+    (world, ground, body)
+}
+
+/// Highest point a ball dropped from y = 2 reaches after its first bounce on ground.
+pub(crate) fn bounce_height(world: &mut World, material: Material) -> f32 {
+    world.add_body(Body::platform_rectangle_aabb(0., -0.5, 20., 1., DEFAULT));
+    let ball = world.add_body(Body::circle(0., 2., 0.3, material));
+
+    let mut bounced = false;
+    let mut highest = f32::MIN;
+
+    for _ in 0..120 {
+        world.update();
+        let body = &world.get_bodies()[ball];
+
+        bounced |= body.transform.velocity.y > 0.;
+        if bounced {
+            highest = highest.max(body.transform.location.y);
+        }
+    }
+
+    highest
+}
+
+/// Adds a static square sensor and returns its index.
+pub(crate) fn sensor(world: &mut World, x: f32, y: f32, size: f32) -> usize {
+    let mut sensor = Body::platform_rectangle_aabb(x, y, size, size, DEFAULT);
+    sensor.is_sensor = true;
+    world.add_body(sensor)
+}